@@ -4,9 +4,11 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 use registry_stake::{
     msg::{
-        CreateOrUpdateConfig, CreateRequestInfo, EpochInfoResponse, ExecuteMsg, InstantiateMsg,
-        QueryMsg, RecurringFeeAmountResponse, RequestInfoResponse, RequestsResponse,
-        StakeAmountResponse, StakesResponse, StateResponse,
+        BlacklistResponse, CreateOrUpdateConfig, CreateRequestInfo, CurrentExecutionResponse,
+        EpochInfoResponse, ExecuteMsg, InstantiateMsg, PendingRewardsResponse, QueryMsg,
+        RecurringFeeAmountResponse, RequestExecutionsResponse, RequestInfoResponse,
+        RequestsResponse, StakeAmountResponse, StakesResponse, StateResponse, TreasuryResponse,
+        UnbondingResponse,
     },
     state::{Config, State},
 };
@@ -22,10 +24,16 @@ fn main() {
     export_schema(&schema_for!(QueryMsg), &out_dir);
     export_schema(&schema_for!(RequestInfoResponse), &out_dir);
     export_schema(&schema_for!(RequestsResponse), &out_dir);
+    export_schema(&schema_for!(RequestExecutionsResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
     export_schema(&schema_for!(StakeAmountResponse), &out_dir);
+    export_schema(&schema_for!(UnbondingResponse), &out_dir);
+    export_schema(&schema_for!(PendingRewardsResponse), &out_dir);
+    export_schema(&schema_for!(TreasuryResponse), &out_dir);
+    export_schema(&schema_for!(CurrentExecutionResponse), &out_dir);
     export_schema(&schema_for!(RecurringFeeAmountResponse), &out_dir);
     export_schema(&schema_for!(StakesResponse), &out_dir);
+    export_schema(&schema_for!(BlacklistResponse), &out_dir);
     export_schema(&schema_for!(EpochInfoResponse), &out_dir);
     export_schema(&schema_for!(CreateOrUpdateConfig), &out_dir);
     export_schema(&schema_for!(CreateRequestInfo), &out_dir);
@@ -2,36 +2,49 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply,
-    ReplyOn, Response, StdResult, SubMsg, SubMsgResult, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, Event, MessageInfo, Order, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg,
+    SubMsgResult, Uint128, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Bound, Map};
 
 use autonomy::asset::{Asset, AssetInfo};
 use autonomy::error::CommonError;
 use autonomy::helper::zero_string;
 use autonomy::types::OrderBy;
-use cw_utils::must_pay;
+use cw_utils::{must_pay, Duration, Scheduled};
 use semver::Version;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
 use crate::msg::{
-    BlacklistResponse, CreateOrUpdateConfig, CreateRequestInfo, Cw20HookMsg, EpochInfoResponse,
-    ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, RecurringFeeAmountResponse,
-    RequestInfoResponse, RequestsResponse, StakeAmountResponse, StakesResponse, StateResponse,
+    BlacklistResponse, CreateOrUpdateConfig, CreateRequestInfo, CurrentExecution,
+    CurrentExecutionResponse, Cw20HookMsg, EpochInfoResponse, ExecuteMsg, InstantiateMsg,
+    MigrateMsg, PendingRewardsResponse, QueryMsg, RecurringFeeAmountResponse,
+    RequestExecutionsResponse, RequestInfoResponse, RequestsResponse, StakeAmountResponse,
+    StakesResponse, StateResponse, TreasuryResponse, UnbondingResponse, UpdateRequestInfo,
 };
 use crate::state::{
-    read_requests, Config, Request, State, ADMIN, BLACKLIST, CONFIG, NEW_ADMIN, RECURRING_BALANCE,
-    REQUESTS, STAKE_BALANCE, STATE,
+    find_stake_slot, update_stake_tree, EpochActivity, LegacyRequest, LegacyStakes, RewardIndex,
+    StakerRewards, UnbondingEntry, EPOCH_ACTIVITY, LEGACY_REQUESTS, LEGACY_STAKES, MAX_STAKERS,
+    REWARD_INDEXES, SLOT_STAKERS, STAKER_REWARDS, STAKER_SLOTS, TREASURY_BALANCE,
+    TREASURY_WITHDRAWN, UNBONDING,
+};
+use crate::state::{
+    read_request_executions, read_requests, read_requests_by_target, read_requests_by_user,
+    requests, Config, ExecutingRequest, RandomnessSource, RecurringSchedule, Request,
+    RequestExecution, RequestStatus, State, ADMIN, BLACKLIST, CONFIG, EXECUTING_REQUESTS,
+    NEW_ADMIN, RECURRING_BALANCE, REQUEST_EXECUTIONS, STAKE_BALANCE, STATE,
 };
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "autonomy-registry-stake";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-/// Hard cap of stakes count
-const MAX_STAKES: u32 = 10000;
+/// Denominator of values in bps
+const BPS_DENOMINATOR: u64 = 10000;
 
 /// ## Description
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
@@ -40,15 +53,15 @@ const MAX_STAKES: u32 = 10000;
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
 ///
-/// * **_env** is an object of type [`Env`].
+/// * **env** is an object of type [`Env`].
 ///
-/// * **_info** is an object of type [`MessageInfo`].
+/// * **info** is an object of type [`MessageInfo`].
 /// * **msg** is a message of type [`InstantiateMsg`] which contains the basic settings for creating the contract.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -62,6 +75,21 @@ pub fn instantiate(
         auto,
         stake_amount,
         blocks_in_epoch,
+        expiry_bounty_bps,
+        staker_grace_blocks,
+        open_grace_blocks,
+        clear_grace_blocks: _,
+        randomness_source,
+        unbonding_period,
+        clear_unbonding_period: _,
+        slash_bps,
+        slash_notice_blocks,
+        slash_bounty_bps,
+        treasury,
+        clear_treasury: _,
+        staker_fee_bps,
+        protocol_fee_bps,
+        hook_tokens,
     } = msg.config;
 
     // All fields should be available
@@ -91,18 +119,40 @@ pub fn instantiate(
         auto,
         stake_amount: stake_amount.unwrap(),
         blocks_in_epoch: blocks_in_epoch.unwrap(),
+        expiry_bounty_bps: _validate_bps("expiry_bounty_bps", expiry_bounty_bps.unwrap_or(0))?,
+        staker_grace_blocks,
+        open_grace_blocks,
+        randomness_source: randomness_source.unwrap_or_default(),
+        unbonding_period,
+        slash_bps: _validate_bps("slash_bps", slash_bps.unwrap_or(0))?,
+        slash_notice_blocks: slash_notice_blocks.unwrap_or(blocks_in_epoch.unwrap() / 2),
+        slash_bounty_bps: _validate_bps("slash_bounty_bps", slash_bounty_bps.unwrap_or(0))?,
+        treasury: treasury
+            .map(|treasury| deps.api.addr_validate(&treasury))
+            .transpose()?,
+        staker_fee_bps: _validate_bps("staker_fee_bps", staker_fee_bps.unwrap_or(0))?,
+        protocol_fee_bps: protocol_fee_bps.unwrap_or(0),
+        hook_tokens: hook_tokens
+            .unwrap_or_default()
+            .iter()
+            .map(|token| deps.api.addr_validate(token))
+            .collect::<StdResult<_>>()?,
     };
+    _validate_grace_blocks(&config)?;
+    _validate_fee_shares(&config)?;
 
-    let state = State {
+    let mut state = State {
         curr_executing_request_id: u64::MAX,
         next_request_id: 0,
         last_epoch: 0,
         total_requests: 0,
         executor: zero_string(),
-        stakes: vec![],
+        num_stakers: 0,
         total_staked: Uint128::zero(),
         total_recurring_fee: Uint128::zero(),
+        entropy: Binary::default(),
     };
+    _mix_entropy(&mut state, &env, &info.sender);
 
     CONFIG.save(deps.storage, &config)?;
     STATE.save(deps.storage, &state)?;
@@ -128,6 +178,78 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
 
         // If state structure changed in any contract version in the way migration is needed, it
         // should occur here
+
+        // 0.5.0 replaces the input asset of requests by a list,
+        // read them before the requests are saved again by the structure without the field
+        let legacy_input_assets: Vec<(u64, Asset)> = if storage_version < Version::new(0, 5, 0) {
+            LEGACY_REQUESTS
+                .range(deps.storage, None, None, OrderBy::Asc.into())
+                .filter_map(|item| match item {
+                    Ok((id, LegacyRequest { input_asset })) => {
+                        input_asset.map(|asset| Ok((id, asset)))
+                    }
+                    Err(err) => Some(Err(err)),
+                })
+                .collect::<StdResult<_>>()?
+        } else {
+            vec![]
+        };
+
+        // 0.2.0 indexes requests by user and target, build indexes of the stored requests
+        if storage_version < Version::new(0, 2, 0) {
+            let stored: Vec<(u64, Request)> = requests()
+                .range(deps.storage, None, None, OrderBy::Asc.into())
+                .collect::<StdResult<_>>()?;
+            for (id, request) in stored {
+                requests().replace(deps.storage, id, Some(&request), None)?;
+            }
+        }
+
+        // 0.3.0 replaces the stakes vector of the state by the stake tree
+        if storage_version < Version::new(0, 3, 0) {
+            let LegacyStakes { stakes } = LEGACY_STAKES.load(deps.storage)?;
+            let mut state = STATE.load(deps.storage)?;
+            for staker in stakes {
+                let staker = deps.api.addr_validate(&staker)?;
+                let balance = STAKE_BALANCE
+                    .load(deps.storage, &staker)
+                    .unwrap_or_default();
+                if balance.is_zero() || STAKER_SLOTS.has(deps.storage, &staker) {
+                    continue;
+                }
+                state.num_stakers += 1;
+                STAKER_SLOTS.save(deps.storage, &staker, &state.num_stakers)?;
+                SLOT_STAKERS.save(deps.storage, state.num_stakers, &staker)?;
+                update_stake_tree(deps.storage, state.num_stakers, Uint128::zero(), balance)?;
+            }
+            STATE.save(deps.storage, &state)?;
+        }
+
+        // 0.4.0 stores the escrowed fee in requests, backfill it by the current config
+        if storage_version < Version::new(0, 4, 0) {
+            let config = CONFIG.load(deps.storage)?;
+            let stored: Vec<(u64, Request)> = requests()
+                .range(deps.storage, None, None, OrderBy::Asc.into())
+                .collect::<StdResult<_>>()?;
+            for (id, mut request) in stored {
+                if request.is_recurring || request.fee.is_some() {
+                    continue;
+                }
+                request.fee = Some(Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: config.fee_denom.clone(),
+                    },
+                    amount: config.fee_amount,
+                });
+                requests().save(deps.storage, id, &request)?;
+            }
+        }
+
+        for (id, input_asset) in legacy_input_assets {
+            let mut request = requests().load(deps.storage, id)?;
+            request.input_assets = vec![input_asset];
+            requests().save(deps.storage, id, &request)?;
+        }
     }
     Ok(Response::default())
 }
@@ -155,9 +277,26 @@ pub fn execute(
         ExecuteMsg::UpdateConfig { config } => update_config(deps, env, info, config),
 
         // Registry
-        ExecuteMsg::CreateRequest { request_info } => create_request(deps, env, info, request_info),
+        ExecuteMsg::CreateRequest { request_info } => {
+            let sender = info.sender.clone();
+            create_request(deps, env, info, &sender, request_info, None)
+        }
+        ExecuteMsg::UpdateRequest { id, request_info } => {
+            update_request(deps, env, info, id, request_info)
+        }
+        ExecuteMsg::TransferRequest {
+            id,
+            new_owner,
+            require_accept,
+        } => transfer_request(deps, env, info, id, new_owner, require_accept),
+        ExecuteMsg::AcceptRequestTransfer { id } => accept_request_transfer(deps, env, info, id),
+        ExecuteMsg::CancelRequestTransfer { id } => cancel_request_transfer(deps, env, info, id),
         ExecuteMsg::CancelRequest { id } => cancel_request(deps, env, info, id),
         ExecuteMsg::ExecuteRequest { id } => execute_request(deps, env, info, id),
+        ExecuteMsg::ExecuteRequests { ids, skip_failed } => {
+            execute_requests(deps, env, info, ids, skip_failed)
+        }
+        ExecuteMsg::ForwardInputAssets {} => forward_input_assets(deps, env, info),
         ExecuteMsg::DepositRecurringFee { recurring_count } => {
             deposit_recurring_fee(deps, info, recurring_count)
         }
@@ -168,8 +307,13 @@ pub fn execute(
         // Staking
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::StakeDenom { num_stakes } => receive_denom(deps, env, info, num_stakes),
-        ExecuteMsg::Unstake { idxs } => unstake(deps, env, info, idxs),
-        ExecuteMsg::UpdateExecutor {} => update_executor(deps, env),
+        ExecuteMsg::Unstake { num_stakes } => unstake(deps, env, info, num_stakes),
+        ExecuteMsg::WithdrawUnbonded {} => withdraw_unbonded(deps, env, info),
+        ExecuteMsg::ReportPendingRequest { id } => report_pending_request(deps, env, info, id),
+        ExecuteMsg::ReportMissedEpoch { epoch } => report_missed_epoch(deps, env, info, epoch),
+        ExecuteMsg::ClaimRewards {} => claim_rewards(deps, env, info),
+        ExecuteMsg::WithdrawTreasury {} => withdraw_treasury(deps, env, info),
+        ExecuteMsg::UpdateExecutor {} => update_executor(deps, env, info),
 
         // Blacklist
         ExecuteMsg::AddToBlacklist { addrs } => add_to_blacklist(deps, env, info, addrs),
@@ -211,6 +355,21 @@ pub fn update_config(
         auto,
         stake_amount,
         blocks_in_epoch,
+        expiry_bounty_bps,
+        staker_grace_blocks,
+        open_grace_blocks,
+        clear_grace_blocks,
+        randomness_source,
+        unbonding_period,
+        clear_unbonding_period,
+        slash_bps,
+        slash_notice_blocks,
+        slash_bounty_bps,
+        treasury,
+        clear_treasury,
+        staker_fee_bps,
+        protocol_fee_bps,
+        hook_tokens,
     } = new_config;
 
     if auto.is_some() || stake_amount.is_some() {
@@ -225,6 +384,51 @@ pub fn update_config(
     config.fee_amount = fee_amount.unwrap_or(config.fee_amount);
     config.fee_denom = fee_denom.unwrap_or(config.fee_denom);
     config.blocks_in_epoch = blocks_in_epoch.unwrap_or(config.blocks_in_epoch);
+    if let Some(expiry_bounty_bps) = expiry_bounty_bps {
+        config.expiry_bounty_bps = _validate_bps("expiry_bounty_bps", expiry_bounty_bps)?;
+    }
+    if clear_grace_blocks == Some(true) {
+        config.staker_grace_blocks = None;
+        config.open_grace_blocks = None;
+    }
+    if staker_grace_blocks.is_some() {
+        config.staker_grace_blocks = staker_grace_blocks;
+    }
+    if open_grace_blocks.is_some() {
+        config.open_grace_blocks = open_grace_blocks;
+    }
+    config.randomness_source = randomness_source.unwrap_or(config.randomness_source);
+    if clear_unbonding_period == Some(true) {
+        config.unbonding_period = None;
+    }
+    if unbonding_period.is_some() {
+        config.unbonding_period = unbonding_period;
+    }
+    if let Some(slash_bps) = slash_bps {
+        config.slash_bps = _validate_bps("slash_bps", slash_bps)?;
+    }
+    config.slash_notice_blocks = slash_notice_blocks.unwrap_or(config.slash_notice_blocks);
+    if let Some(slash_bounty_bps) = slash_bounty_bps {
+        config.slash_bounty_bps = _validate_bps("slash_bounty_bps", slash_bounty_bps)?;
+    }
+    if clear_treasury == Some(true) {
+        config.treasury = None;
+    }
+    if let Some(treasury) = treasury {
+        config.treasury = Some(deps.api.addr_validate(&treasury)?);
+    }
+    if let Some(staker_fee_bps) = staker_fee_bps {
+        config.staker_fee_bps = _validate_bps("staker_fee_bps", staker_fee_bps)?;
+    }
+    config.protocol_fee_bps = protocol_fee_bps.unwrap_or(config.protocol_fee_bps);
+    if let Some(hook_tokens) = hook_tokens {
+        config.hook_tokens = hook_tokens
+            .iter()
+            .map(|token| deps.api.addr_validate(token))
+            .collect::<StdResult<_>>()?;
+    }
+    _validate_grace_blocks(&config)?;
+    _validate_fee_shares(&config)?;
 
     CONFIG.save(deps.storage, &config)?;
 
@@ -261,6 +465,9 @@ pub fn claim_admin(
 /// ## Description
 /// Creates a new request
 /// * Funds should cover the execution fee and the asset for the request execution
+/// * Token input asset is pulled from the sender unless it's received through the CW20 hook,
+///   then the execution fee is paid from the recurring fee balance of the sender
+/// * Overpaid and unrelated funds are refunded to the sender
 /// * Executor for the current epoch is set for this request
 ///   if there's no executor, anyone can execute the request
 /// * Request Id increases from zero by one
@@ -272,12 +479,18 @@ pub fn claim_admin(
 ///
 /// * **info** is an object of type [`MessageInfo`].
 ///
+/// * **sender** is the creator of the request, which is an object of type [`Addr`].
+///
 /// * **request_info** is an object of type [`CreateRequestInfo`].
+///
+/// * **received_asset** is the token received through the CW20 hook, which is an object of type [`Option<Asset>`].
 pub fn create_request(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    sender: &Addr,
     request_info: CreateRequestInfo,
+    received_asset: Option<Asset>,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
     let mut state = STATE.load(deps.storage)?;
@@ -291,13 +504,47 @@ pub fn create_request(
         return Err(ContractError::TargetBlacklisted {});
     }
 
-    // Recurring requests can't have input assets
-    if request_info.is_recurring && request_info.input_asset.is_some() {
-        return Err(ContractError::NoInputAssetForRecurring {});
+    // Single input asset goes ahead of the others
+    let input_assets: Vec<Asset> = request_info
+        .input_asset
+        .into_iter()
+        .chain(request_info.input_assets)
+        .collect();
+    let catch_failure = request_info.catch_failure.unwrap_or(false);
+    _validate_input_assets(&input_assets, request_info.is_recurring)?;
+
+    // Only recurring requests can have a schedule
+    if let Some(schedule) = &request_info.recurring_schedule {
+        _validate_recurring_schedule(schedule, request_info.is_recurring, 0, None)?;
+    }
+
+    // Request shouldn't be expired already
+    if let Some(expires_at) = request_info.expires_at {
+        if expires_at.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+    }
+
+    // Received token should be one of the input assets
+    if let Some(received_asset) = &received_asset {
+        if !input_assets.contains(received_asset) {
+            return Err(ContractError::InvalidInputAssets {});
+        }
     }
 
     // If this is not recurring request, funds should contain execution fee
-    if !request_info.is_recurring {
+    if !request_info.is_recurring && received_asset.is_some() {
+        // Token sender can't attach funds, pay from the recurring fee balance
+        let mut balance = RECURRING_BALANCE
+            .load(deps.storage, sender)
+            .unwrap_or_default();
+        if balance < config.fee_amount {
+            return Err(ContractError::InsufficientRecurringFee {});
+        }
+        balance -= config.fee_amount;
+        state.total_recurring_fee -= config.fee_amount;
+        RECURRING_BALANCE.save(deps.storage, sender, &balance)?;
+    } else if !request_info.is_recurring {
         if let Some(fee_fund_index) = funds.iter().position(|f| f.denom == config.fee_denom) {
             // Fee amount should be enough
             if funds[fee_fund_index].amount < config.fee_amount {
@@ -313,47 +560,55 @@ pub fn create_request(
     }
 
     // Check fund tokens will be used for request
-    if let Some(input_asset) = request_info.input_asset.clone() {
-        match input_asset.info {
-            AssetInfo::NativeToken { denom } => {
-                if let Some(asset_index) = funds.iter().position(|f| f.denom == denom) {
-                    // Check if actual amount matches with amount passed by params
-                    if funds[asset_index].amount < input_asset.amount {
-                        return Err(ContractError::InvalidInputAssets {});
-                    }
-                } else {
-                    return Err(ContractError::InvalidInputAssets {});
-                }
-            }
-            AssetInfo::Token { contract_addr } => {
-                msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: contract_addr.to_string(),
-                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                        owner: info.sender.to_string(),
-                        recipient: env.contract.address.to_string(),
-                        amount: input_asset.amount,
-                    })?,
-                    funds: vec![],
-                }));
-            }
-        }
-    }
+    let escrowed_assets: Vec<Asset> = input_assets
+        .iter()
+        .filter(|asset| Some(*asset) != received_asset.as_ref())
+        .cloned()
+        .collect();
+    msgs.extend(_escrow_assets(&env, sender, &mut funds, &escrowed_assets)?);
+
+    // Refund overpaid and unrelated funds
+    msgs.extend(_refund_funds(sender, funds));
 
     // Create and save request struct
     let id = state.next_request_id;
     let request = Request {
-        user: info.sender.to_string(),
+        user: sender.to_string(),
         target: target_addr.to_string(),
         msg: request_info.msg,
-        input_asset: request_info.input_asset,
+        input_assets,
         is_recurring: request_info.is_recurring,
         created_at: env.block.time.seconds(),
+        catch_failure,
+        status: RequestStatus::Pending,
+        valid_from: request_info.valid_from,
+        expires_at: request_info.expires_at,
+        recurring_schedule: request_info.recurring_schedule,
+        last_executed_at: None,
+        executions_done: 0,
+        fee: if request_info.is_recurring {
+            None
+        } else {
+            Some(Asset {
+                info: AssetInfo::NativeToken {
+                    denom: config.fee_denom.clone(),
+                },
+                amount: config.fee_amount,
+            })
+        },
+        pending_owner: None,
     };
 
     state.next_request_id += 1;
     state.total_requests += 1;
+    _mix_entropy(&mut state, &env, sender);
+
+    // Request is pending during the current epoch if it can be executed already
+    if _validate_executable(deps.as_ref(), &env, &config, &request).is_ok() {
+        _record_pending(deps.storage, &env, &config, &state)?;
+    }
 
-    REQUESTS.save(deps.storage, id, &request)?;
+    requests().save(deps.storage, id, &request)?;
     STATE.save(deps.storage, &state)?;
 
     Ok(Response::new().add_messages(msgs).add_attributes(vec![
@@ -362,7 +617,7 @@ pub fn create_request(
         attr("user", request.user),
         attr("target", request.target),
         attr("msg", request.msg.to_string()),
-        attr("asset", format!("{:?}", request.input_asset)),
+        attr("assets", format!("{:?}", request.input_assets)),
         attr(
             "is_recurring",
             if request.is_recurring {
@@ -376,76 +631,135 @@ pub fn create_request(
 }
 
 /// ## Description
-/// Cancel the request with [`id`]. Returns a [`ContractError`] on failure.
-/// * Return the escrowed assets for the request execution.
-/// * Return execution fee.
-/// * Remove request from the storage.
+/// Update the pending request with [`id`] in place. Returns a [`ContractError`] on failure.
+/// * Escrow only the increase of each input asset and refund only the decrease.
+/// * The target is checked against the blacklist again.
+/// * A pending transfer is cancelled, so the new owner can't accept a request changed meanwhile.
 ///
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
 ///
-/// * **_env** is an object of type [`Env`].
+/// * **env** is an object of type [`Env`].
 ///
 /// * **info** is an object of type [`MessageInfo`].
 ///
 /// * **id** is the request id, which an object of type [`u64`].
 ///
+/// * **request_info** is an object of type [`UpdateRequestInfo`].
+///
 /// ## Executor
 /// Only the owner of the request can execute this.
-pub fn cancel_request(
+pub fn update_request(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     id: u64,
+    request_info: UpdateRequestInfo,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-
-    let request = REQUESTS.load(deps.storage, id)?;
+    let mut request = requests().load(deps.storage, id)?;
 
     // Validate owner
-    let request_owner = deps.api.addr_validate(request.user.as_str())?;
-    if request_owner != info.sender {
+    if deps.api.addr_validate(request.user.as_str())? != info.sender {
         return Err(CommonError::Unauthorized {}.into());
     }
 
-    // Returun escrowed tokens
-    let recipient = deps.api.addr_validate(&request.user)?;
-    let mut msgs: Vec<CosmosMsg> = vec![];
+    // Failed requests can only be cancelled, expired ones can be cancelled by anyone
+    if request.status == RequestStatus::Failed {
+        return Err(ContractError::RequestFailed {});
+    }
+    if let Some(expires_at) = request.expires_at {
+        if expires_at.is_expired(&env.block) {
+            return Err(ContractError::RequestExpired {});
+        }
+    }
 
-    if let Some(input_asset) = request.input_asset {
-        msgs.push(input_asset.into_msg(&deps.querier, recipient.clone())?);
+    // Check if the target got blacklisted meanwhile
+    let target_addr = deps.api.addr_validate(&request.target)?;
+    if BLACKLIST.has(deps.storage, &target_addr) {
+        return Err(ContractError::TargetBlacklisted {});
     }
 
-    // Return fee asset if not recurring request
-    if !request.is_recurring {
-        let fee_asset = Asset {
-            info: AssetInfo::NativeToken {
-                denom: config.fee_denom,
-            },
-            amount: config.fee_amount,
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut funds = info.funds.clone();
+
+    if let Some(input_assets) = request_info.input_assets {
+        _validate_input_assets(&input_assets, request.is_recurring)?;
+
+        let escrowed_amount = |assets: &[Asset], asset_info: &AssetInfo| {
+            assets
+                .iter()
+                .find(|asset| &asset.info == asset_info)
+                .map(|asset| asset.amount)
+                .unwrap_or_default()
         };
-        msgs.push(fee_asset.into_msg(&deps.querier, recipient)?);
+
+        // Escrow the increased amounts
+        let top_ups: Vec<Asset> = input_assets
+            .iter()
+            .filter_map(|asset| {
+                let escrowed = escrowed_amount(&request.input_assets, &asset.info);
+                (asset.amount > escrowed).then(|| Asset {
+                    info: asset.info.clone(),
+                    amount: asset.amount - escrowed,
+                })
+            })
+            .collect();
+        msgs.extend(_escrow_assets(&env, &info.sender, &mut funds, &top_ups)?);
+
+        // Return the decreased and removed amounts
+        for escrowed in request.input_assets.iter() {
+            let amount = escrowed_amount(&input_assets, &escrowed.info);
+            if escrowed.amount > amount {
+                let refund_asset = Asset {
+                    info: escrowed.info.clone(),
+                    amount: escrowed.amount - amount,
+                };
+                msgs.push(refund_asset.into_msg(&deps.querier, info.sender.clone())?);
+            }
+        }
+
+        request.input_assets = input_assets;
     }
 
-    // Remove request
-    let mut state = STATE.load(deps.storage)?;
-    state.total_requests -= 1;
-    STATE.save(deps.storage, &state)?;
+    if let Some(schedule) = request_info.recurring_schedule {
+        _validate_recurring_schedule(
+            &schedule,
+            request.is_recurring,
+            request.executions_done,
+            request.last_executed_at,
+        )?;
+        request.recurring_schedule = Some(schedule);
+    }
+
+    if let Some(msg) = request_info.msg {
+        request.msg = msg;
+    }
+    request.pending_owner = None;
 
-    REQUESTS.remove(deps.storage, id);
+    // Refund unused funds
+    msgs.extend(_refund_funds(&info.sender, funds));
 
-    Ok(Response::new().add_messages(msgs).add_attributes(vec![
-        attr("action", "cancel_request"),
-        attr("id", id.to_string()),
-    ]))
+    requests().save(deps.storage, id, &request)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(vec![
+            attr("action", "update_request"),
+            attr("id", id.to_string()),
+        ])
+        .add_event(Event::new("request_updated").add_attributes(vec![
+            attr("id", id.to_string()),
+            attr("user", request.user),
+            attr("msg", request.msg.to_string()),
+            attr("assets", format!("{:?}", request.input_assets)),
+        ])))
 }
 
 /// ## Description
-/// Execute request with [`id`]. Returns a [`ContractError`] on failure.
-/// * Forward escrowed assets and call the target contract.
-/// * Transfer execution fees to the executor.
-/// * Fails if executor doesn't match.
-/// * Request remains if it's recurring.
+/// Transfer the request with [`id`] to [`new_owner`]. Returns a [`ContractError`] on failure.
+/// * Refunds and recurring fees follow the new owner once the transfer is done.
+/// * If [`require_accept`], or the request is recurring, the new owner should accept the
+///   transfer first, so recurring fees are never charged from someone who didn't accept them.
 ///
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
@@ -456,164 +770,1106 @@ pub fn cancel_request(
 ///
 /// * **id** is the request id, which an object of type [`u64`].
 ///
+/// * **new_owner** is an object of type [`String`].
+///
+/// * **require_accept** is an object of type [`Option<bool>`].
+///
 /// ## Executor
-/// Only the excutor of the current epoch can execute this.
-pub fn execute_request(
+/// Only the owner of the request can execute this.
+pub fn transfer_request(
     deps: DepsMut,
-    env: Env,
+    _env: Env,
     info: MessageInfo,
     id: u64,
+    new_owner: String,
+    require_accept: Option<bool>,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    let request = REQUESTS.load(deps.storage, id)?;
-    let target = deps.api.addr_validate(&request.target)?;
-    let mut state = STATE.load(deps.storage)?;
-
-    // Check if blacklisted
-    if BLACKLIST.has(deps.storage, &target) {
-        return Err(ContractError::TargetBlacklisted {});
-    }
-
-    // Validate executor
-    let cur_epoch = env.block.height / config.blocks_in_epoch * config.blocks_in_epoch;
-    if cur_epoch != state.last_epoch {
-        _update_executor(&mut state, env, config.blocks_in_epoch);
-        STATE.save(deps.storage, &state)?;
-    }
-
-    if !state.executor.is_empty() {
-        let executor = deps.api.addr_validate(&state.executor)?;
-        if executor != info.sender {
-            return Err(ContractError::InvalidExecutor {});
-        }
-    }
-
-    // If recurring request, deduct fee from the pool
-    if request.is_recurring {
-        let user = deps.api.addr_validate(&request.user)?;
-        let mut balance = RECURRING_BALANCE
-            .load(deps.storage, &user)
-            .unwrap_or_default();
-        if balance < config.fee_amount {
-            return Err(ContractError::InsufficientRecurringFee {});
-        }
-        balance -= config.fee_amount;
-        state.total_recurring_fee -= config.fee_amount;
-        RECURRING_BALANCE.save(deps.storage, &user, &balance)?;
-    }
-
-    // Update current executing request id
-    state.curr_executing_request_id = id;
-    STATE.save(deps.storage, &state)?;
-
-    // Forward escrowed assets and execute contract
-    let mut msgs = vec![];
+    let mut request = requests().load(deps.storage, id)?;
 
-    if let Some(input_asset) = request.input_asset.clone() {
-        msgs.push(SubMsg {
-            id: 0,
-            msg: input_asset.into_msg(&deps.querier, target)?,
-            gas_limit: None,
-            reply_on: ReplyOn::Never,
-        });
-        msgs.push(SubMsg {
-            id: 1,
-            msg: CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: request.target.to_string(),
-                funds: vec![],
-                msg: request.msg,
-            }),
-            gas_limit: None,
-            reply_on: ReplyOn::Success,
-        });
+    // Validate owner
+    if deps.api.addr_validate(request.user.as_str())? != info.sender {
+        return Err(CommonError::Unauthorized {}.into());
     }
 
-    // Transfer fee to executor
-    let fee_asset = Asset {
-        info: AssetInfo::NativeToken {
-            denom: config.fee_denom,
-        },
-        amount: config.fee_amount,
-    };
-    msgs.push(SubMsg {
-        id: 0,
-        msg: fee_asset.into_msg(&deps.querier, info.sender.clone())?,
-        gas_limit: None,
-        reply_on: ReplyOn::Never,
-    });
-
-    // Remove request
-    if !request.is_recurring {
-        let mut state = STATE.load(deps.storage)?;
-        state.total_requests -= 1;
-        STATE.save(deps.storage, &state)?;
-        REQUESTS.remove(deps.storage, id);
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    let pending = require_accept.unwrap_or(false) || request.is_recurring;
+    if pending {
+        request.pending_owner = Some(new_owner.to_string());
+    } else {
+        request.user = new_owner.to_string();
+        request.pending_owner = None;
     }
+    requests().save(deps.storage, id, &request)?;
 
-    Ok(Response::new().add_submessages(msgs).add_attributes(vec![
-        attr("action", "execute_request"),
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "transfer_request"),
         attr("id", id.to_string()),
-        attr("executor", info.sender),
+        attr("new_owner", new_owner),
+        attr("pending", pending.to_string()),
     ]))
 }
 
 /// ## Description
-/// Deposit recurring fee. Returns a [`ContractError`] on failure.
-/// * Fails [`recurring_count`] is invalid.
+/// Accept the pending transfer of the request with [`id`]. Returns a [`ContractError`] on failure.
 ///
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
 ///
+/// * **env** is an object of type [`Env`].
+///
 /// * **info** is an object of type [`MessageInfo`].
 ///
-/// * **recurring_count** is count of recurring, defines the deposit amount
-/// * it is an object of type [`u64`].
-pub fn deposit_recurring_fee(
+/// * **id** is the request id, which an object of type [`u64`].
+///
+/// ## Executor
+/// Only the pending owner of the request can execute this.
+pub fn accept_request_transfer(
     deps: DepsMut,
+    _env: Env,
     info: MessageInfo,
-    recurring_count: u64,
+    id: u64,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-    let deposit_amount = config
-        .fee_amount
-        .checked_mul(Uint128::from(recurring_count))?;
+    let mut request = requests().load(deps.storage, id)?;
 
-    // Check if `recurring_count` is zero
-    if recurring_count == 0 || deposit_amount != must_pay(&info, &config.fee_denom)? {
-        return Err(ContractError::InvalidRecurringCount {});
+    if request.pending_owner.as_deref() != Some(info.sender.as_str()) {
+        return Err(CommonError::Unauthorized {}.into());
     }
 
-    // Update storage
-    let mut state = STATE.load(deps.storage)?;
-    let mut balance = RECURRING_BALANCE
-        .load(deps.storage, &info.sender)
-        .unwrap_or_default();
-
-    state.total_recurring_fee += deposit_amount;
-    balance += deposit_amount;
-
-    STATE.save(deps.storage, &state)?;
-    RECURRING_BALANCE.save(deps.storage, &info.sender, &balance)?;
+    request.user = info.sender.to_string();
+    request.pending_owner = None;
+    requests().save(deps.storage, id, &request)?;
 
     Ok(Response::new().add_attributes(vec![
-        attr("action", "deposit_recurring_fee"),
-        attr("recurring_count", recurring_count.to_string()),
-        attr("amount", deposit_amount.to_string()),
+        attr("action", "accept_request_transfer"),
+        attr("id", id.to_string()),
+        attr("user", info.sender),
     ]))
 }
 
 /// ## Description
-/// Withdraw recurring fee. Returns a [`ContractError`] on failure.
-/// * Fails [`recurring_count`] is invalid.
+/// Cancel the pending transfer of the request with [`id`]. Returns a [`ContractError`] on failure.
 ///
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
 ///
+/// * **env** is an object of type [`Env`].
+///
 /// * **info** is an object of type [`MessageInfo`].
 ///
-/// * **recurring_count** is count of recurring, defines the deposit amount
-/// * it is an object of type [`u64`].
-pub fn withdraw_recurring_fee(
+/// * **id** is the request id, which an object of type [`u64`].
+///
+/// ## Executor
+/// Only the owner of the request can execute this.
+pub fn cancel_request_transfer(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut request = requests().load(deps.storage, id)?;
+
+    // Validate owner
+    if deps.api.addr_validate(request.user.as_str())? != info.sender {
+        return Err(CommonError::Unauthorized {}.into());
+    }
+
+    if request.pending_owner.take().is_none() {
+        return Err(ContractError::NoPendingTransfer {});
+    }
+    requests().save(deps.storage, id, &request)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "cancel_request_transfer"),
+        attr("id", id.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Cancel the request with [`id`]. Returns a [`ContractError`] on failure.
+/// * Return the escrowed assets for the request execution.
+/// * Return execution fee.
+/// * If someone else cancels an expired request, pay the bounty from the fee.
+/// * Remove request from the storage.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **id** is the request id, which an object of type [`u64`].
+///
+/// ## Executor
+/// Only the owner of the request can execute this, unless the request expired.
+pub fn cancel_request(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let request = requests().load(deps.storage, id)?;
+
+    // Validate owner, anyone can cancel expired requests
+    let request_owner = deps.api.addr_validate(request.user.as_str())?;
+    let expired = request
+        .expires_at
+        .is_some_and(|expires_at| expires_at.is_expired(&env.block));
+    if request_owner != info.sender && !expired {
+        return Err(CommonError::Unauthorized {}.into());
+    }
+
+    // Returun escrowed tokens
+    let recipient = request_owner;
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut bounty = Uint128::zero();
+
+    for input_asset in request.input_assets {
+        msgs.push(input_asset.into_msg(&deps.querier, recipient.clone())?);
+    }
+
+    // Return escrowed fee asset, failed requests already paid the fee to the executor
+    if let (Some(fee), RequestStatus::Pending) = (request.fee, request.status) {
+        if recipient != info.sender {
+            bounty = fee
+                .amount
+                .multiply_ratio(config.expiry_bounty_bps, BPS_DENOMINATOR);
+        }
+
+        let fee_info = fee.info;
+        let refund_asset = Asset {
+            info: fee_info.clone(),
+            amount: fee.amount - bounty,
+        };
+        if !refund_asset.amount.is_zero() {
+            msgs.push(refund_asset.into_msg(&deps.querier, recipient.clone())?);
+        }
+
+        let bounty_asset = Asset {
+            info: fee_info,
+            amount: bounty,
+        };
+        if !bounty_asset.amount.is_zero() {
+            msgs.push(bounty_asset.into_msg(&deps.querier, info.sender.clone())?);
+        }
+    }
+
+    // Remove request
+    let mut state = STATE.load(deps.storage)?;
+    state.total_requests -= 1;
+    STATE.save(deps.storage, &state)?;
+
+    requests().remove(deps.storage, id)?;
+
+    let mut attrs = vec![attr("action", "cancel_request"), attr("id", id.to_string())];
+    if recipient != info.sender {
+        attrs.push(attr("bounty", bounty.to_string()));
+    }
+
+    Ok(Response::new().add_messages(msgs).add_attributes(attrs))
+}
+
+/// ## Description
+/// Execute request with [`id`]. Returns a [`ContractError`] on failure.
+/// * Forward escrowed assets and call the target contract.
+/// * Transfer execution fees to the executor.
+/// * Fails if executor doesn't match.
+/// * Request remains if it's recurring.
+/// * If the request catches failures, the target call replies always
+///   so that the failure is recorded instead of reverting.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **id** is the request id, which an object of type [`u64`].
+///
+/// ## Executor
+/// Only the excutor of the current epoch can execute this.
+pub fn execute_request(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    _validate_executor(&mut deps, &env, &info, &config, &mut state)?;
+
+    let mut executing = vec![];
+    let (mut msgs, fee_asset) = _prepare_execution(
+        &mut deps,
+        &env,
+        &info,
+        &config,
+        &mut state,
+        id,
+        &mut executing,
+    )?;
+
+    _record_execution(deps.storage, &state, &info.sender)?;
+
+    // Update current executing request id
+    state.curr_executing_request_id = id;
+    STATE.save(deps.storage, &state)?;
+    EXECUTING_REQUESTS.save(deps.storage, &executing)?;
+
+    // Transfer fee to executor
+    if !fee_asset.amount.is_zero() {
+        msgs.push(SubMsg {
+            id: 0,
+            msg: fee_asset.into_msg(&deps.querier, info.sender.clone())?,
+            gas_limit: None,
+            reply_on: ReplyOn::Never,
+        });
+    }
+
+    Ok(Response::new().add_submessages(msgs).add_attributes(vec![
+        attr("action", "execute_request"),
+        attr("id", id.to_string()),
+        attr("executor", info.sender),
+    ]))
+}
+
+/// ## Description
+/// Execute many requests with [`ids`] in one transaction. Returns a [`ContractError`] on failure.
+/// * Executor is validated once.
+/// * Execution fees are transferred to the executor at once.
+/// * Fails if any id is repeated.
+/// * If `skip_failed` is set, requests failing validation or not found are skipped instead of reverting.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **ids** is the list of request ids, which is an object of type [`Vec<u64>`].
+///
+/// * **skip_failed** is an object of type [`bool`].
+///
+/// ## Executor
+/// Only the excutor of the current epoch can execute this.
+pub fn execute_requests(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    ids: Vec<u64>,
+    skip_failed: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let mut state = STATE.load(deps.storage)?;
+
+    _validate_executor(&mut deps, &env, &info, &config, &mut state)?;
+
+    for (i, id) in ids.iter().enumerate() {
+        if ids[..i].contains(id) {
+            return Err(ContractError::DuplicateRequestIds {});
+        }
+    }
+
+    let mut executing = vec![];
+    let mut msgs = vec![];
+    let mut executed = vec![];
+    let mut skipped = vec![];
+    let mut fee_assets: Vec<Asset> = vec![];
+    for id in ids {
+        match _prepare_execution(
+            &mut deps,
+            &env,
+            &info,
+            &config,
+            &mut state,
+            id,
+            &mut executing,
+        ) {
+            Ok((target_msgs, fee)) => {
+                msgs.extend(target_msgs);
+                executed.push(id.to_string());
+                match fee_assets.iter_mut().find(|asset| asset.info == fee.info) {
+                    Some(asset) => asset.amount += fee.amount,
+                    None => fee_assets.push(fee),
+                }
+            }
+            Err(err) if skip_failed && _is_skippable(&err) => skipped.push(id.to_string()),
+            Err(err) => return Err(err),
+        }
+    }
+
+    // Update current executing request id
+    if let Some(first) = executing.first() {
+        _record_execution(deps.storage, &state, &info.sender)?;
+        state.curr_executing_request_id = first.id;
+    }
+    STATE.save(deps.storage, &state)?;
+    EXECUTING_REQUESTS.save(deps.storage, &executing)?;
+
+    // Transfer fees to executor at once
+    for fee_asset in fee_assets {
+        if fee_asset.amount.is_zero() {
+            continue;
+        }
+        msgs.push(SubMsg {
+            id: 0,
+            msg: fee_asset.into_msg(&deps.querier, info.sender.clone())?,
+            gas_limit: None,
+            reply_on: ReplyOn::Never,
+        });
+    }
+
+    Ok(Response::new().add_submessages(msgs).add_attributes(vec![
+        attr("action", "execute_requests"),
+        attr("executor", info.sender),
+        attr("executed", executed.join(",")),
+        attr("skipped", skipped.join(",")),
+    ]))
+}
+
+/// ## Description
+/// Forward the input assets of the request being executed along with its call.
+/// Returns a [`ContractError`] on failure.
+/// * CW20 input assets are transferred to the target, then it's called with native input assets.
+/// * As the registry dispatches this as the sub message of the execution, the transfers are
+///   reverted if the call fails.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// ## Executor
+/// Only the registry itself can execute this.
+pub fn forward_input_assets(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(CommonError::Unauthorized {}.into());
+    }
+
+    // Request being executed is the first one waiting for its reply
+    let executing = EXECUTING_REQUESTS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let ExecutingRequest { id, request, .. } =
+        executing.first().ok_or(CommonError::Unauthorized {})?;
+
+    Ok(Response::new()
+        .add_messages(_forward_msgs(request)?)
+        .add_attributes(vec![
+            attr("action", "forward_input_assets"),
+            attr("id", id.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Validates the sender can execute requests, updating the executor if a new epoch started.
+/// * Anyone can execute if nobody staked.
+/// * The executor of the current epoch can execute.
+/// * Any staker can execute after `staker_grace_blocks` from the epoch start.
+/// * Anyone can execute after `open_grace_blocks` from the epoch start.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **state** is an object of type [`State`].
+fn _validate_executor(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    config: &Config,
+    state: &mut State,
+) -> Result<(), ContractError> {
+    let cur_epoch = env.block.height / config.blocks_in_epoch * config.blocks_in_epoch;
+    if cur_epoch != state.last_epoch {
+        _update_executor(deps.storage, state, env, config)?;
+    }
+    _mix_entropy(state, env, &info.sender);
+
+    // Nobody staked
+    if state.executor.is_empty() {
+        return Ok(());
+    }
+
+    let executor = deps.api.addr_validate(&state.executor)?;
+    if executor == info.sender {
+        return Ok(());
+    }
+
+    // Executor is idle for a while
+    let blocks_since_epoch = env.block.height - cur_epoch;
+    if config
+        .open_grace_blocks
+        .is_some_and(|grace| blocks_since_epoch >= grace)
+    {
+        return Ok(());
+    }
+    if config
+        .staker_grace_blocks
+        .is_some_and(|grace| blocks_since_epoch >= grace)
+        && STAKER_SLOTS.has(deps.storage, &info.sender)
+    {
+        return Ok(());
+    }
+
+    Err(ContractError::InvalidExecutor {})
+}
+
+/// ## Description
+/// Records the executor of the current epoch executed a request
+///
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **state** is an object of type [`State`].
+///
+/// * **sender** is an object of type [`Addr`].
+fn _record_execution(storage: &mut dyn Storage, state: &State, sender: &Addr) -> StdResult<()> {
+    if let Some(mut activity) = EPOCH_ACTIVITY.may_load(storage, state.last_epoch)? {
+        if activity.executor == *sender && !activity.executed {
+            activity.executed = true;
+            EPOCH_ACTIVITY.save(storage, state.last_epoch, &activity)?;
+        }
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Validates the request can be executed at the current block. Returns a [`ContractError`] otherwise.
+/// * Target should not be blacklisted, and the request should not be failed
+/// * Current block should be in the execution window of the request
+/// * Recurring request should be due, and the pool of the owner should cover the fee
+///
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **request** is an object of type [`Request`].
+fn _validate_executable(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    request: &Request,
+) -> Result<(), ContractError> {
+    let target = deps.api.addr_validate(&request.target)?;
+
+    // Check if blacklisted
+    if BLACKLIST.has(deps.storage, &target) {
+        return Err(ContractError::TargetBlacklisted {});
+    }
+
+    // Failed requests can't be executed again
+    if request.status == RequestStatus::Failed {
+        return Err(ContractError::RequestFailed {});
+    }
+
+    // Validate execution window
+    if let Some(valid_from) = request.valid_from {
+        if !valid_from.is_triggered(&env.block) {
+            return Err(ContractError::RequestNotValidYet {});
+        }
+    }
+    if let Some(expires_at) = request.expires_at {
+        if expires_at.is_expired(&env.block) {
+            return Err(ContractError::RequestExpired {});
+        }
+    }
+
+    // Validate recurring interval
+    if let (Some(schedule), Some(last_executed_at)) =
+        (&request.recurring_schedule, request.last_executed_at)
+    {
+        if !(last_executed_at + schedule.interval)?.is_triggered(&env.block) {
+            return Err(ContractError::RequestNotDue {});
+        }
+    }
+
+    // Validate recurring fee pool
+    if request.is_recurring {
+        let user = deps.api.addr_validate(&request.user)?;
+        let balance = RECURRING_BALANCE
+            .load(deps.storage, &user)
+            .unwrap_or_default();
+        if balance < config.fee_amount {
+            return Err(ContractError::InsufficientRecurringFee {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Records requests were executable during the current epoch, so its executor can be slashed
+/// for missing it
+///
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **state** is an object of type [`State`].
+fn _record_pending(
+    storage: &mut dyn Storage,
+    env: &Env,
+    config: &Config,
+    state: &State,
+) -> StdResult<()> {
+    if state.last_epoch != env.block.height / config.blocks_in_epoch * config.blocks_in_epoch {
+        return Ok(());
+    }
+    if let Some(mut activity) = EPOCH_ACTIVITY.may_load(storage, state.last_epoch)? {
+        if activity.pending_since.is_none() {
+            activity.pending_since = Some(env.block.height);
+            EPOCH_ACTIVITY.save(storage, state.last_epoch, &activity)?;
+        }
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Returns true if the execution failed on [`_validate_executable`] or the request was not found,
+/// before anything was written to the storage, so that it can be skipped.
+/// ## Params
+/// * **err** is an object of type [`ContractError`].
+fn _is_skippable(err: &ContractError) -> bool {
+    matches!(
+        err,
+        ContractError::TargetBlacklisted {}
+            | ContractError::RequestFailed {}
+            | ContractError::RequestNotValidYet {}
+            | ContractError::RequestExpired {}
+            | ContractError::RequestNotDue {}
+            | ContractError::InsufficientRecurringFee {}
+            | ContractError::Std(StdError::NotFound { .. })
+    )
+}
+
+/// ## Description
+/// Validates the request with [`id`] for execution and builds the target call.
+/// Validation errors are returned before anything is written to the storage,
+/// any later error must revert the whole transaction.
+/// * Recurring fee is deducted from the pool of the owner.
+/// * `staker_fee_bps` of the fee is distributed to stakers, `protocol_fee_bps` is accrued to the treasury,
+///   and the rest is returned for the executor.
+/// * Request is removed unless it's recurring and below its execution cap.
+/// * Request is queued in `executing` until the reply of the target call.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **config** is an object of type [`Config`].
+///
+/// * **state** is an object of type [`State`].
+///
+/// * **id** is the request id, which an object of type [`u64`].
+///
+/// * **executing** is the queue of requests being executed.
+fn _prepare_execution(
+    deps: &mut DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    config: &Config,
+    state: &mut State,
+    id: u64,
+    executing: &mut Vec<ExecutingRequest>,
+) -> Result<(Vec<SubMsg>, Asset), ContractError> {
+    let mut request = requests().load(deps.storage, id)?;
+    _validate_executable(deps.as_ref(), env, config, &request)?;
+
+    // Execute the target contract, forwarding escrowed assets along with the call
+    let msgs = vec![SubMsg {
+        id: 1,
+        msg: _target_msg(env, &request)?,
+        gas_limit: None,
+        reply_on: if request.catch_failure {
+            ReplyOn::Always
+        } else {
+            ReplyOn::Success
+        },
+    }];
+
+    // Fee is escrowed in the request, or deducted from the pool if recurring
+    let fee = match request.fee.clone() {
+        Some(fee) => fee,
+        None => Asset {
+            info: AssetInfo::NativeToken {
+                denom: config.fee_denom.clone(),
+            },
+            amount: config.fee_amount,
+        },
+    };
+    if request.is_recurring {
+        let user = deps.api.addr_validate(&request.user)?;
+        let mut balance = RECURRING_BALANCE
+            .load(deps.storage, &user)
+            .unwrap_or_default();
+        if balance < fee.amount {
+            return Err(ContractError::InsufficientRecurringFee {});
+        }
+        balance -= fee.amount;
+        state.total_recurring_fee -= fee.amount;
+        RECURRING_BALANCE.save(deps.storage, &user, &balance)?;
+    }
+
+    // Share fee with stakers in the denom it was paid
+    let staker_fee = if state.total_staked.is_zero() {
+        Uint128::zero()
+    } else {
+        let staker_fee = fee
+            .amount
+            .multiply_ratio(config.staker_fee_bps, BPS_DENOMINATOR);
+        _distribute_rewards(deps.storage, state, &fee.info.to_string(), staker_fee)?;
+        staker_fee
+    };
+
+    // Accrue protocol fee to the treasury in the denom it was paid
+    let protocol_fee = fee
+        .amount
+        .multiply_ratio(config.protocol_fee_bps, BPS_DENOMINATOR);
+    if !protocol_fee.is_zero() {
+        TREASURY_BALANCE.update(
+            deps.storage,
+            &fee.info.to_string(),
+            |balance| -> StdResult<_> { Ok(balance.unwrap_or_default() + protocol_fee) },
+        )?;
+    }
+
+    // Track executions
+    request.last_executed_at = Some(match request.recurring_schedule {
+        Some(RecurringSchedule {
+            interval: Duration::Time(_),
+            ..
+        }) => Scheduled::AtTime(env.block.time),
+        _ => Scheduled::AtHeight(env.block.height),
+    });
+    request.executions_done += 1;
+
+    // Remove request once it's done
+    let max_executions = request
+        .recurring_schedule
+        .as_ref()
+        .and_then(|schedule| schedule.max_executions);
+    if !request.is_recurring || max_executions.is_some_and(|max| request.executions_done >= max) {
+        state.total_requests -= 1;
+        requests().remove(deps.storage, id)?;
+    } else {
+        requests().save(deps.storage, id, &request)?;
+    }
+
+    executing.push(ExecutingRequest {
+        id,
+        request,
+        executor: info.sender.to_string(),
+    });
+
+    let executor_fee = Asset {
+        info: fee.info,
+        amount: fee.amount - staker_fee - protocol_fee,
+    };
+
+    Ok((msgs, executor_fee))
+}
+
+/// ## Description
+/// Validates the grace blocks and the slash notice blocks end within the epoch,
+/// and anyone can't execute earlier than stakers
+///
+/// ## Params
+/// * **config** is an object of type [`Config`].
+fn _validate_grace_blocks(config: &Config) -> Result<(), ContractError> {
+    for (param_name, grace_blocks) in [
+        ("staker_grace_blocks", config.staker_grace_blocks),
+        ("open_grace_blocks", config.open_grace_blocks),
+    ] {
+        if let Some(grace_blocks) = grace_blocks {
+            if grace_blocks >= config.blocks_in_epoch {
+                return Err(CommonError::InvalidParam {
+                    param_name: param_name.to_string(),
+                    invalid_value: grace_blocks.to_string(),
+                    predicate: format!("< {}", config.blocks_in_epoch),
+                }
+                .into());
+            }
+        }
+    }
+    if config.slash_notice_blocks > config.blocks_in_epoch {
+        return Err(CommonError::InvalidParam {
+            param_name: "slash_notice_blocks".to_string(),
+            invalid_value: config.slash_notice_blocks.to_string(),
+            predicate: format!("<= {}", config.blocks_in_epoch),
+        }
+        .into());
+    }
+
+    if let (Some(staker_grace_blocks), Some(open_grace_blocks)) =
+        (config.staker_grace_blocks, config.open_grace_blocks)
+    {
+        if open_grace_blocks < staker_grace_blocks {
+            return Err(CommonError::InvalidParam {
+                param_name: "open_grace_blocks".to_string(),
+                invalid_value: open_grace_blocks.to_string(),
+                predicate: format!(">= {staker_grace_blocks}"),
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Validates the shares of the execution fee don't exceed the fee
+///
+/// ## Params
+/// * **config** is an object of type [`Config`].
+fn _validate_fee_shares(config: &Config) -> Result<(), ContractError> {
+    let max_protocol_fee_bps = BPS_DENOMINATOR - config.staker_fee_bps;
+    if config.protocol_fee_bps > max_protocol_fee_bps {
+        return Err(CommonError::InvalidParam {
+            param_name: "protocol_fee_bps".to_string(),
+            invalid_value: config.protocol_fee_bps.to_string(),
+            predicate: format!("<= {max_protocol_fee_bps}"),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Validates a value in bps, it can't exceed [`BPS_DENOMINATOR`]
+///
+/// ## Params
+/// * **param_name** is the name of the param.
+///
+/// * **bps** is the value in bps.
+fn _validate_bps(param_name: &str, bps: u64) -> Result<u64, ContractError> {
+    if bps > BPS_DENOMINATOR {
+        return Err(CommonError::InvalidParam {
+            param_name: param_name.to_string(),
+            invalid_value: bps.to_string(),
+            predicate: format!("<= {BPS_DENOMINATOR}"),
+        }
+        .into());
+    }
+    Ok(bps)
+}
+
+/// ## Description
+/// Builds the msg calling the target of a request
+/// * A single CW20 input asset is sent to the target with the request msg embedded
+/// * CW20 input assets combined with other assets are forwarded by the registry calling itself,
+///   so the transfers are reverted along with a failed call
+/// * Otherwise native input assets are attached as funds of the call
+///
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **request** is an object of type [`Request`].
+fn _target_msg(env: &Env, request: &Request) -> StdResult<CosmosMsg> {
+    let has_tokens = request
+        .input_assets
+        .iter()
+        .any(|asset| matches!(asset.info, AssetInfo::Token { .. }));
+    let msg = match request.input_assets.as_slice() {
+        [Asset {
+            info: AssetInfo::Token { contract_addr },
+            amount,
+        }] => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: request.target.clone(),
+                amount: *amount,
+                msg: request.msg.clone(),
+            })?,
+            funds: vec![],
+        },
+        _ if has_tokens => WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::ForwardInputAssets {})?,
+            funds: vec![],
+        },
+        _ => _call_msg(request),
+    };
+
+    Ok(CosmosMsg::Wasm(msg))
+}
+
+/// ## Description
+/// Builds the msgs forwarding the input assets of a request along with its call,
+/// CW20 input assets are transferred to the target before the call
+///
+/// ## Params
+/// * **request** is an object of type [`Request`].
+fn _forward_msgs(request: &Request) -> StdResult<Vec<CosmosMsg>> {
+    let mut msgs = vec![];
+    for asset in &request.input_assets {
+        if let AssetInfo::Token { contract_addr } = &asset.info {
+            msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: request.target.clone(),
+                    amount: asset.amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+    }
+    msgs.push(CosmosMsg::Wasm(_call_msg(request)));
+
+    Ok(msgs)
+}
+
+/// ## Description
+/// Builds the call of the target of a request, with native input assets attached as funds
+///
+/// ## Params
+/// * **request** is an object of type [`Request`].
+fn _call_msg(request: &Request) -> WasmMsg {
+    let funds = request
+        .input_assets
+        .iter()
+        .filter_map(|asset| match &asset.info {
+            AssetInfo::NativeToken { denom } => Some(Coin {
+                denom: denom.clone(),
+                amount: asset.amount,
+            }),
+            AssetInfo::Token { .. } => None,
+        })
+        .collect();
+
+    WasmMsg::Execute {
+        contract_addr: request.target.clone(),
+        msg: request.msg.clone(),
+        funds,
+    }
+}
+
+/// ## Description
+/// Validates the input assets of a request. Returns a [`ContractError`] on failure.
+/// * Fails if an asset is given more than once or with zero amount.
+/// * Fails if the request is recurring and has input assets.
+///
+/// ## Params
+/// * **input_assets** is an object of type [`Vec<Asset>`].
+///
+/// * **is_recurring** is an object of type [`bool`].
+fn _validate_input_assets(input_assets: &[Asset], is_recurring: bool) -> Result<(), ContractError> {
+    for (i, asset) in input_assets.iter().enumerate() {
+        if asset.amount.is_zero() {
+            return Err(ContractError::ZeroInputAsset {});
+        }
+        if input_assets[..i]
+            .iter()
+            .any(|other| other.info == asset.info)
+        {
+            return Err(ContractError::DuplicateInputAssets {});
+        }
+    }
+
+    // Recurring requests can't have input assets
+    if is_recurring && !input_assets.is_empty() {
+        return Err(ContractError::NoInputAssetForRecurring {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Validates the schedule of a request. Returns a [`ContractError`] on failure.
+/// * Fails if the request is not recurring.
+/// * Fails if `max_executions` is not above [`executions_done`].
+/// * Fails if the interval is not of the kind of [`last_executed_at`], which it's added to.
+///
+/// ## Params
+/// * **schedule** is an object of type [`RecurringSchedule`].
+///
+/// * **is_recurring** is an object of type [`bool`].
+///
+/// * **executions_done** is an object of type [`u64`].
+///
+/// * **last_executed_at** is an object of type [`Option<Scheduled>`].
+fn _validate_recurring_schedule(
+    schedule: &RecurringSchedule,
+    is_recurring: bool,
+    executions_done: u64,
+    last_executed_at: Option<Scheduled>,
+) -> Result<(), ContractError> {
+    if !is_recurring {
+        return Err(ContractError::ScheduleForNonRecurring {});
+    }
+    let predicate = match (last_executed_at, schedule.interval) {
+        (Some(Scheduled::AtHeight(_)), Duration::Time(_)) => Some("in blocks"),
+        (Some(Scheduled::AtTime(_)), Duration::Height(_)) => Some("in seconds"),
+        _ => None,
+    };
+    if let Some(predicate) = predicate {
+        return Err(CommonError::InvalidParam {
+            param_name: "interval".to_string(),
+            invalid_value: schedule.interval.to_string(),
+            predicate: predicate.to_string(),
+        }
+        .into());
+    }
+    if let Some(max_executions) = schedule.max_executions {
+        if max_executions <= executions_done {
+            return Err(CommonError::InvalidParam {
+                param_name: "max_executions".to_string(),
+                invalid_value: max_executions.to_string(),
+                predicate: format!("> {}", executions_done),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Takes the assets into escrow, native ones from [`funds`] and CW20 ones from [`owner`].
+/// Returns the transfer messages or a [`ContractError`] if funds don't cover the assets.
+///
+/// ## Params
+/// * **env** is an object of type [`Env`].
+///
+/// * **owner** is an object of type [`Addr`].
+///
+/// * **funds** is an object of type [`Vec<Coin>`].
+///
+/// * **assets** is an object of type [`Vec<Asset>`].
+fn _escrow_assets(
+    env: &Env,
+    owner: &Addr,
+    funds: &mut [Coin],
+    assets: &[Asset],
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    for asset in assets.iter() {
+        match &asset.info {
+            AssetInfo::NativeToken { denom } => {
+                if let Some(asset_index) = funds.iter().position(|f| &f.denom == denom) {
+                    // Check if actual amount matches with amount passed by params
+                    if funds[asset_index].amount < asset.amount {
+                        return Err(ContractError::InvalidInputAssets {});
+                    }
+                    funds[asset_index].amount -= asset.amount;
+                } else {
+                    return Err(ContractError::InvalidInputAssets {});
+                }
+            }
+            AssetInfo::Token { contract_addr } => {
+                msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: owner.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: asset.amount,
+                    })?,
+                    funds: vec![],
+                }));
+            }
+        }
+    }
+    Ok(msgs)
+}
+
+/// ## Description
+/// Returns the message refunding the non-zero [`funds`] to [`recipient`], if any.
+///
+/// ## Params
+/// * **recipient** is an object of type [`Addr`].
+///
+/// * **funds** is an object of type [`Vec<Coin>`].
+fn _refund_funds(recipient: &Addr, mut funds: Vec<Coin>) -> Option<CosmosMsg> {
+    funds.retain(|f| !f.amount.is_zero());
+    (!funds.is_empty()).then(|| {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: funds,
+        })
+    })
+}
+
+/// ## Description
+/// Deposit recurring fee. Returns a [`ContractError`] on failure.
+/// * Fails [`recurring_count`] is invalid.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **recurring_count** is count of recurring, defines the deposit amount
+/// * it is an object of type [`u64`].
+pub fn deposit_recurring_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    recurring_count: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let deposit_amount = config
+        .fee_amount
+        .checked_mul(Uint128::from(recurring_count))?;
+
+    // Check if `recurring_count` is zero
+    if recurring_count == 0 || deposit_amount != must_pay(&info, &config.fee_denom)? {
+        return Err(ContractError::InvalidRecurringCount {});
+    }
+
+    // Update storage
+    let mut state = STATE.load(deps.storage)?;
+    let mut balance = RECURRING_BALANCE
+        .load(deps.storage, &info.sender)
+        .unwrap_or_default();
+
+    state.total_recurring_fee += deposit_amount;
+    balance += deposit_amount;
+
+    STATE.save(deps.storage, &state)?;
+    RECURRING_BALANCE.save(deps.storage, &info.sender, &balance)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "deposit_recurring_fee"),
+        attr("recurring_count", recurring_count.to_string()),
+        attr("amount", deposit_amount.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Withdraw recurring fee. Returns a [`ContractError`] on failure.
+/// * Fails [`recurring_count`] is invalid.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **recurring_count** is count of recurring, defines the deposit amount
+/// * it is an object of type [`u64`].
+pub fn withdraw_recurring_fee(
     deps: DepsMut,
     info: MessageInfo,
     recurring_count: u64,
@@ -656,7 +1912,8 @@ pub fn withdraw_recurring_fee(
 }
 
 /// ## Description
-/// Process when we receive AUTO tokens for staking. Returns a [`ContractError`] on failure.
+/// Process when we receive AUTO tokens for staking, or allowed tokens for a new request.
+/// Returns a [`ContractError`] on failure.
 ///
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
@@ -691,6 +1948,28 @@ pub fn receive_cw20(
             let cw20_sender = deps.api.addr_validate(&cw20_msg.sender)?;
             stake(deps, env, info, &cw20_sender, num_stakes, cw20_msg.amount)
         }
+        Ok(Cw20HookMsg::CreateRequest { request_info }) => {
+            // Only allowed tokens can report the sender, whose balance pays the fee
+            if !config.hook_tokens.contains(&info.sender) {
+                return Err(CommonError::Unauthorized {}.into());
+            }
+
+            let cw20_sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            let received_asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender.clone(),
+                },
+                amount: cw20_msg.amount,
+            };
+            create_request(
+                deps,
+                env,
+                info,
+                &cw20_sender,
+                request_info,
+                Some(received_asset),
+            )
+        }
         Err(_) => Err(ContractError::DataShouldBeGiven {}),
     }
 }
@@ -725,157 +2004,629 @@ pub fn receive_denom(
 }
 
 /// ## Description
-/// Update stakes for new stakings. Returns a [`ContractError`] on failure.
-/// * Add user address to array `num_stakes` times
-/// * Update user's and total staking balances
-/// * Update executor
+/// Update stakes for new stakings. Returns a [`ContractError`] on failure.
+/// * Update user's and total staking balances
+/// * Assign a slot in the stake tree to a new staker
+/// * Update executor
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **_info** is an object of type [`MessageInfo`].
+///
+/// * **sender** is the staker, which is an object of type [`Addr`].
+///
+/// * **num_stakes** is the number of stakings, which is an object of type [`u64`].
+///
+/// * **amount** is the staking amount, which is an object of type [`Uint128`].
+pub fn stake(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    sender: &Addr,
+    num_stakes: u64,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Validate amount
+    if config.stake_amount * Uint128::from(num_stakes) != amount {
+        return Err(ContractError::InvalidStakeInfo {});
+    }
+
+    // Update executor
+    let mut state = STATE.load(deps.storage)?;
+    _update_executor(deps.storage, &mut state, &env, &config)?;
+    _mix_entropy(&mut state, &env, sender);
+
+    // Add amount to stake balance
+    let balance = STAKE_BALANCE.load(deps.storage, sender).unwrap_or_default() + amount;
+    _update_stake_balance(deps.storage, &mut state, sender, balance)?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "stake"),
+        attr("user", sender),
+        attr("num_stakes", num_stakes.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Unstake AUTO. Returns a [`ContractError`] on failure.
+/// * Deduct `num_stakes` stakings from the balance of the caller
+/// * Move unstaked AUTO into the unbonding queue if the unbonding period is set,
+///   otherwise return it
+/// * Updates executor
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **num_stakes** is the number of stakings, which is an object of type [`u64`].
+pub fn unstake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    num_stakes: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Update executor
+    let mut state = STATE.load(deps.storage)?;
+    _update_executor(deps.storage, &mut state, &env, &config)?;
+    _mix_entropy(&mut state, &env, &info.sender);
+
+    // Validate and update stake balance
+    let amount = Uint128::from(num_stakes) * config.stake_amount;
+    let balance = STAKE_BALANCE
+        .load(deps.storage, &info.sender)
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Err(ContractError::InvalidStakeInfo {});
+    }
+    if amount > balance {
+        return Err(ContractError::InsufficientStake {});
+    }
+    _update_stake_balance(deps.storage, &mut state, &info.sender, balance - amount)?;
+    STATE.save(deps.storage, &state)?;
+
+    let mut res = Response::new().add_attributes(vec![
+        attr("action", "unstake"),
+        attr("user", info.sender.clone()),
+        attr("count", num_stakes.to_string()),
+    ]);
+
+    // Queue unstaked assets until the unbonding period passes
+    if let Some(unbonding_period) = config.unbonding_period {
+        let release_at = unbonding_period.after(&env.block);
+        let mut entries = UNBONDING
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+        entries.push(UnbondingEntry { amount, release_at });
+        UNBONDING.save(deps.storage, &info.sender, &entries)?;
+
+        return Ok(res.add_attribute("release_at", release_at.to_string()));
+    }
+
+    // Return assets
+    let return_asset = Asset {
+        info: config.auto,
+        amount,
+    };
+    res = res.add_message(return_asset.into_msg(&deps.querier, info.sender)?);
+
+    Ok(res)
+}
+
+/// ## Description
+/// Withdraw unstaked AUTO of which unbonding period passed. Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+pub fn withdraw_unbonded(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let (released, pending): (Vec<UnbondingEntry>, Vec<UnbondingEntry>) = UNBONDING
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|entry| entry.release_at.is_expired(&env.block));
+    let amount: Uint128 = released.iter().map(|entry| entry.amount).sum();
+    if amount.is_zero() {
+        return Err(ContractError::NoUnbondedStake {});
+    }
+
+    if pending.is_empty() {
+        UNBONDING.remove(deps.storage, &info.sender);
+    } else {
+        UNBONDING.save(deps.storage, &info.sender, &pending)?;
+    }
+
+    // Return assets
+    let return_asset = Asset {
+        info: config.auto,
+        amount,
+    };
+
+    Ok(Response::new()
+        .add_message(return_asset.into_msg(&deps.querier, info.sender.clone())?)
+        .add_attributes(vec![
+            attr("action", "withdraw_unbonded"),
+            attr("user", info.sender),
+            attr("amount", amount.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Record the request with [`id`] can be executed during the current epoch. Returns a [`ContractError`] on failure.
+/// * Requests created executable are recorded already, this records the ones becoming executable later
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **id** is the request id, which an object of type [`u64`].
+pub fn report_pending_request(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut state = STATE.load(deps.storage)?;
+    _update_executor(deps.storage, &mut state, &env, &config)?;
+    _mix_entropy(&mut state, &env, &info.sender);
+    STATE.save(deps.storage, &state)?;
+
+    let request = requests().load(deps.storage, id)?;
+    _validate_executable(deps.as_ref(), &env, &config, &request)?;
+    _record_pending(deps.storage, &env, &config, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "report_pending_request"),
+        attr("id", id.to_string()),
+        attr("epoch", state.last_epoch.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Slash the executor who missed the `epoch`. Returns a [`ContractError`] on failure.
+/// * The epoch should be over, with requests pending since at least `slash_notice_blocks` before
+///   its end and none executed by its executor
+/// * `slash_bps` of the stake balance and the unbonding AUTO of the executor is slashed, once per epoch
+/// * `slash_bounty_bps` of the slashed AUTO is paid to the reporter and the rest to the treasury,
+///   only the share of the reporter is slashed if the treasury is not set
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **epoch** is the start height of the epoch, which is an object of type [`u64`].
+pub fn report_missed_epoch(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    epoch: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Validate the epoch was missed
+    let cur_epoch = env.block.height / config.blocks_in_epoch * config.blocks_in_epoch;
+    let executor = match EPOCH_ACTIVITY.may_load(deps.storage, epoch)? {
+        Some(EpochActivity {
+            pending_since: Some(pending_since),
+            executed: false,
+            executor,
+        }) if epoch < cur_epoch
+            && epoch + config.blocks_in_epoch >= pending_since + config.slash_notice_blocks =>
+        {
+            executor
+        }
+        _ => return Err(ContractError::EpochNotMissed {}),
+    };
+
+    // The executor keeps the share of the treasury if it's not set
+    let slash_bps = match config.treasury {
+        Some(_) => config.slash_bps,
+        None => config.slash_bps * config.slash_bounty_bps / BPS_DENOMINATOR,
+    };
+    let balance = STAKE_BALANCE
+        .load(deps.storage, &executor)
+        .unwrap_or_default();
+    let stake_slashed = balance.multiply_ratio(slash_bps, BPS_DENOMINATOR);
+
+    // Unbonding AUTO is slashed as well, so unstaking doesn't escape the penalty
+    let mut entries = UNBONDING
+        .may_load(deps.storage, &executor)?
+        .unwrap_or_default();
+    let mut unbonding_slashed = Uint128::zero();
+    for entry in entries.iter_mut() {
+        let slashed = entry.amount.multiply_ratio(slash_bps, BPS_DENOMINATOR);
+        entry.amount -= slashed;
+        unbonding_slashed += slashed;
+    }
+    let amount = stake_slashed + unbonding_slashed;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToSlash {});
+    }
+
+    // Slash the executor
+    let mut state = STATE.load(deps.storage)?;
+    _update_stake_balance(deps.storage, &mut state, &executor, balance - stake_slashed)?;
+    if !entries.is_empty() {
+        UNBONDING.save(deps.storage, &executor, &entries)?;
+    }
+    STATE.save(deps.storage, &state)?;
+    EPOCH_ACTIVITY.remove(deps.storage, epoch);
+
+    let bounty = match config.treasury {
+        Some(_) => amount.multiply_ratio(config.slash_bounty_bps, BPS_DENOMINATOR),
+        None => amount,
+    };
+    let mut msgs = vec![];
+    for (receiver, amount) in [
+        (Some(info.sender.clone()), bounty),
+        (config.treasury, amount - bounty),
+    ] {
+        if let Some(receiver) = receiver {
+            if !amount.is_zero() {
+                let asset = Asset {
+                    info: config.auto.clone(),
+                    amount,
+                };
+                msgs.push(asset.into_msg(&deps.querier, receiver)?);
+            }
+        }
+    }
+
+    Ok(Response::new().add_messages(msgs).add_attributes(vec![
+        attr("action", "report_missed_epoch"),
+        attr("epoch", epoch.to_string()),
+        attr("executor", executor),
+        attr("reporter", info.sender),
+        attr("amount", amount.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Claim execution fee rewards of the caller. Returns a [`ContractError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+pub fn claim_rewards(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let balance = STAKE_BALANCE
+        .load(deps.storage, &info.sender)
+        .unwrap_or_default();
+    let mut amount: Vec<Coin> = vec![];
+    for (denom, mut rewards) in _settle_rewards(deps.storage, &info.sender, balance)? {
+        if rewards.pending.is_zero() {
+            continue;
+        }
+        amount.push(Coin {
+            denom: denom.clone(),
+            amount: rewards.pending,
+        });
+        rewards.pending = Uint128::zero();
+        STAKER_REWARDS.save(deps.storage, (&info.sender, &denom), &rewards)?;
+    }
+    if amount.is_empty() {
+        return Err(ContractError::NoRewards {});
+    }
+
+    // Transfer rewards
+    let amount_str = amount
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount,
+        })
+        .add_attributes(vec![
+            attr("action", "claim_rewards"),
+            attr("user", info.sender),
+            attr("amount", amount_str),
+        ]))
+}
+
+/// ## Description
+/// Withdraw accrued protocol fees to the treasury. Returns a [`ContractError`] on failure.
 ///
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
 ///
-/// * **env** is an object of type [`Env`].
-///
-/// * **_info** is an object of type [`MessageInfo`].
-///
-/// * **sender** is the staker, which is an object of type [`Addr`].
+/// * **_env** is an object of type [`Env`].
 ///
-/// * **num_stakes** is the number of stakings, which is an object of type [`u64`].
+/// * **info** is an object of type [`MessageInfo`].
 ///
-/// * **amount** is the staking amount, which is an object of type [`Uint128`].
-pub fn stake(
+/// ## Executor
+/// Only the admin can execute this.
+pub fn withdraw_treasury(
     deps: DepsMut,
-    env: Env,
-    _info: MessageInfo,
-    sender: &Addr,
-    num_stakes: u64,
-    amount: Uint128,
+    _env: Env,
+    info: MessageInfo,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    // Validate amount
-    if config.stake_amount * Uint128::from(num_stakes) != amount {
-        return Err(ContractError::InvalidStakeInfo {});
-    }
-
-    // Update executor
-    let mut state = STATE.load(deps.storage)?;
-    _update_executor(&mut state, env, config.blocks_in_epoch);
-    STATE.save(deps.storage, &state)?;
+    // Only admin can withdraw
+    ADMIN.assert_admin(deps.as_ref(), &info.sender)?;
 
-    // Validate hard cap
-    if state.stakes.len() as u64 + num_stakes > MAX_STAKES as u64 {
-        return Err(ContractError::StakesExceedCap {});
+    let treasury = config.treasury.ok_or(ContractError::TreasuryNotSet {})?;
+    let amount = _treasury_coins(deps.as_ref(), TREASURY_BALANCE)?;
+    if amount.is_empty() {
+        return Err(ContractError::NoProtocolFee {});
     }
-
-    // Update stakes array
-    for _ in 0..num_stakes {
-        state.stakes.push(sender.to_string());
+    for coin in amount.iter() {
+        TREASURY_BALANCE.remove(deps.storage, &coin.denom);
+        TREASURY_WITHDRAWN.update(deps.storage, &coin.denom, |withdrawn| -> StdResult<_> {
+            Ok(withdrawn.unwrap_or_default() + coin.amount)
+        })?;
     }
 
-    // Add amount to stake balance
-    let balance = STAKE_BALANCE.load(deps.storage, sender).unwrap_or_default() + amount;
-    STAKE_BALANCE.save(deps.storage, sender, &balance)?;
-    state.total_staked += amount;
-    STATE.save(deps.storage, &state)?;
+    // Transfer protocol fees
+    let amount_str = amount
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
 
-    Ok(Response::new().add_attributes(vec![
-        attr("action", "stake"),
-        attr("user", sender),
-        attr("num_stakes", num_stakes.to_string()),
-    ]))
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: treasury.to_string(),
+            amount,
+        })
+        .add_attributes(vec![
+            attr("action", "withdraw_treasury"),
+            attr("treasury", treasury),
+            attr("amount", amount_str),
+        ]))
 }
 
 /// ## Description
-/// Unstake AUTO. Returns a [`ContractError`] on failure.
-/// * Remove from stakes array at indexes of [`idxs`]
-/// * Return staked AUTO
-/// * Updates executor
+/// Returns the protocol fees stored in [`coins`], in every denom
 ///
 /// ## Params
-/// * **deps** is an object of type [`DepsMut`].
+/// * **deps** is an object of type [`Deps`].
 ///
-/// * **env** is an object of type [`Env`].
+/// * **coins** is either [`TREASURY_BALANCE`] or [`TREASURY_WITHDRAWN`].
+fn _treasury_coins(deps: Deps, coins: Map<&str, Uint128>) -> StdResult<Vec<Coin>> {
+    coins
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect()
+}
+
+/// ## Description
+/// Sets the stake balance of the `staker`, keeping the stake tree and total stake in sync.
+/// Returns a [`ContractError`] on failure.
+/// * A new staker takes the next slot
+/// * A staker without balance leaves the slot, and the last staker moves into it
 ///
-/// * **info** is an object of type [`MessageInfo`].
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
 ///
-/// * **idxs** is the index array of stakings, which is an object of type [`Vec<u64>`].
-pub fn unstake(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    idxs: Vec<u64>,
-) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-
-    // Update executor
-    let mut state = STATE.load(deps.storage)?;
-    _update_executor(&mut state, env, config.blocks_in_epoch);
-    STATE.save(deps.storage, &state)?;
-
-    // Validate and remove stakes
-    for idx in &idxs {
-        let idx = *idx as usize;
-        if idx >= state.stakes.len() {
-            return Err(ContractError::IdxOutOfBound {});
+/// * **state** is current [`STATE`]
+///
+/// * **staker** is an object of type [`Addr`].
+///
+/// * **balance** is the new stake balance, which is an object of type [`Uint128`].
+fn _update_stake_balance(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    staker: &Addr,
+    balance: Uint128,
+) -> Result<(), ContractError> {
+    let old = STAKE_BALANCE.load(storage, staker).unwrap_or_default();
+    _settle_rewards(storage, staker, old)?;
+
+    match STAKER_SLOTS.may_load(storage, staker)? {
+        None if balance.is_zero() => {}
+        None => {
+            // Validate hard cap
+            if state.num_stakers >= MAX_STAKERS {
+                return Err(ContractError::StakesExceedCap {});
+            }
+            state.num_stakers += 1;
+            STAKER_SLOTS.save(storage, staker, &state.num_stakers)?;
+            SLOT_STAKERS.save(storage, state.num_stakers, staker)?;
+            update_stake_tree(storage, state.num_stakers, old, balance)?;
         }
-        let addr = deps.api.addr_validate(&state.stakes[idx])?;
-        if addr != info.sender {
-            return Err(ContractError::IdxNotYou {});
+        Some(slot) if balance.is_zero() => {
+            update_stake_tree(storage, slot, old, balance)?;
+
+            // Move the last staker into the emptied slot
+            let last = state.num_stakers;
+            if slot != last {
+                let last_staker = SLOT_STAKERS.load(storage, last)?;
+                let last_balance = STAKE_BALANCE.load(storage, &last_staker)?;
+                update_stake_tree(storage, last, last_balance, Uint128::zero())?;
+                update_stake_tree(storage, slot, Uint128::zero(), last_balance)?;
+                STAKER_SLOTS.save(storage, &last_staker, &slot)?;
+                SLOT_STAKERS.save(storage, slot, &last_staker)?;
+            }
+            SLOT_STAKERS.remove(storage, last);
+            STAKER_SLOTS.remove(storage, staker);
+            state.num_stakers -= 1;
         }
-        state.stakes.swap_remove(idx);
+        Some(slot) => update_stake_tree(storage, slot, old, balance)?,
     }
 
-    // Update stake balance
-    let amount = Uint128::from(idxs.len() as u64) * config.stake_amount;
-    let balance = STAKE_BALANCE
-        .load(deps.storage, &info.sender)
-        .unwrap_or_default()
-        - amount;
-    STAKE_BALANCE.save(deps.storage, &info.sender, &balance)?;
-    state.total_staked -= amount;
-    STATE.save(deps.storage, &state)?;
+    state.total_staked = state.total_staked + balance - old;
+    if balance.is_zero() {
+        STAKE_BALANCE.remove(storage, staker);
+    } else {
+        STAKE_BALANCE.save(storage, staker, &balance)?;
+    }
+    Ok(())
+}
 
-    // Return assets
-    let return_asset = Asset {
-        info: config.auto,
-        amount,
-    };
+/// ## Description
+/// Distributes `amount` of `denom` to the stakers pro rata to the stake balances.
+/// The rounding remainder of the reward per stake is carried to the next distribution.
+///
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **state** is an object of type [`State`], with nonzero `total_staked`.
+///
+/// * **denom** is the denom of the rewards.
+///
+/// * **amount** is an object of type [`Uint128`].
+fn _distribute_rewards(
+    storage: &mut dyn Storage,
+    state: &State,
+    denom: &str,
+    amount: Uint128,
+) -> StdResult<()> {
+    let mut index = REWARD_INDEXES.may_load(storage, denom)?.unwrap_or_default();
+    let amount = amount + index.remainder;
+    let reward_per_stake = Decimal::from_ratio(amount, state.total_staked);
+    index.remainder = amount - state.total_staked * reward_per_stake;
+    index.reward_per_stake += reward_per_stake;
+    REWARD_INDEXES.save(storage, denom, &index)
+}
 
-    Ok(Response::new()
-        .add_message(return_asset.into_msg(&deps.querier, info.sender.clone())?)
-        .add_attributes(vec![
-            attr("action", "unstake"),
-            attr("user", info.sender),
-            attr("count", idxs.len().to_string()),
-        ]))
+/// ## Description
+/// Settles execution fee rewards of the `staker` accumulated since the last settlement,
+/// and returns the rewards of every denom.
+/// It should be called before any change of the stake balance.
+///
+/// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
+/// * **staker** is an object of type [`Addr`].
+///
+/// * **balance** is the stake balance since the last settlement, which is an object of type [`Uint128`].
+fn _settle_rewards(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    balance: Uint128,
+) -> StdResult<Vec<(String, StakerRewards)>> {
+    let indexes = REWARD_INDEXES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, RewardIndex)>>>()?;
+
+    let mut settled = vec![];
+    for (denom, index) in indexes {
+        let mut rewards = STAKER_REWARDS
+            .may_load(storage, (staker, &denom))?
+            .unwrap_or_default();
+        rewards.pending += balance * (index.reward_per_stake - rewards.reward_per_stake);
+        rewards.reward_per_stake = index.reward_per_stake;
+        STAKER_REWARDS.save(storage, (staker, &denom), &rewards)?;
+        settled.push((denom, rewards));
+    }
+
+    Ok(settled)
+}
+
+/// ## Description
+/// Mixes the block, the contract address and the `sender` into the hash chain of the state.
+/// It should be called after the executor update, so that the caller can't affect its own selection
+///
+/// ## Params
+/// * **state** is current [`STATE`]
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **sender** is an object of type [`Addr`].
+fn _mix_entropy(state: &mut State, env: &Env, sender: &Addr) {
+    let mut hasher = Sha256::new();
+    hasher.update(state.entropy.as_slice());
+    hasher.update(env.block.height.to_be_bytes());
+    hasher.update(env.block.time.nanos().to_be_bytes());
+    hasher.update(env.contract.address.as_bytes());
+    hasher.update(sender.as_bytes());
+    state.entropy = Binary::from(hasher.finalize().to_vec());
 }
 
 /// ## Description
 /// Util fcn for executor update
 /// * It first checks the executor is set for current epoch
 /// * If not, decide current epoch and set the executor
+/// * Executor is picked randomly, weighted by stake balances
 /// * If nobody staked yet, then executor is set to empty string
 ///
 /// ## Params
+/// * **storage** is an object of type [`Storage`].
+///
 /// * **state** is current [`STATE`]
 ///
 /// * **env** is an object of type [`Env`].
 ///
-/// * **blocks_in_epoch** is block counts included in a single epoch.
-fn _update_executor(state: &mut State, env: Env, blocks_in_epoch: u64) {
-    let last_epoch = env.block.height / blocks_in_epoch * blocks_in_epoch;
+/// * **config** is an object of type [`Config`].
+fn _update_executor(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    env: &Env,
+    config: &Config,
+) -> StdResult<()> {
+    let last_epoch = env.block.height / config.blocks_in_epoch * config.blocks_in_epoch;
     if state.last_epoch != last_epoch {
-        let len = state.stakes.len() as u64;
-
-        if len > 0 {
-            let mut rng = oorandom::Rand64::new(env.block.height as u128);
-            let index = rng.rand_u64() % len;
-            state.executor = state.stakes[index as usize].clone();
+        if !state.total_staked.is_zero() {
+            let seed = match config.randomness_source {
+                RandomnessSource::BlockHeight => env.block.height as u128,
+                RandomnessSource::HashChain => {
+                    let mut hasher = Sha256::new();
+                    hasher.update(state.entropy.as_slice());
+                    hasher.update(env.block.height.to_be_bytes());
+                    let mut seed = [0u8; 16];
+                    seed.copy_from_slice(&hasher.finalize()[..16]);
+                    u128::from_be_bytes(seed)
+                }
+            };
+            let mut rng = oorandom::Rand64::new(seed);
+            let random = (rng.rand_u64() as u128) << 64 | rng.rand_u64() as u128;
+            let point = Uint128::from(random % state.total_staked.u128());
+            let slot = find_stake_slot(storage, point)?;
+            let executor = SLOT_STAKERS.load(storage, slot)?;
+            EPOCH_ACTIVITY.save(
+                storage,
+                last_epoch,
+                &EpochActivity {
+                    executor: executor.clone(),
+                    pending_since: None,
+                    executed: false,
+                },
+            )?;
+            state.executor = executor.to_string();
             state.last_epoch = last_epoch;
         } else {
             state.executor = zero_string();
         }
     }
+    Ok(())
 }
 
 /// ## Description
@@ -888,11 +2639,18 @@ fn _update_executor(state: &mut State, env: Env, blocks_in_epoch: u64) {
 /// * **deps** is an object of type [`DepsMut`].
 ///
 /// * **env** is an object of type [`Env`].
-pub fn update_executor(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+///
+/// * **info** is an object of type [`MessageInfo`].
+pub fn update_executor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     let mut state = STATE.load(deps.storage)?;
-    _update_executor(&mut state, env, config.blocks_in_epoch);
+    _update_executor(deps.storage, &mut state, &env, &config)?;
+    _mix_entropy(&mut state, &env, &info.sender);
     STATE.save(deps.storage, &state)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -981,22 +2739,70 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 }
 
 /// ## Description
-/// Sets the `curr_executing_request_id` back to default value
+/// Records the execution outcome and moves the `curr_executing_request_id` to the next request
+/// being executed, or back to default value
+/// * If the target failed, non-recurring request is put back as `Failed`
+///   so that the owner can cancel it and get the input asset back
+///
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`].
 ///
-/// * **_env** is an object of type [`Env`].
+/// * **env** is an object of type [`Env`].
 ///
-/// * **_msg** is an object of type [`SubMsgResult`].
+/// * **msg** is an object of type [`SubMsgResult`].
 pub fn execute_reply(
     deps: DepsMut,
-    _env: Env,
-    _msg: SubMsgResult,
+    env: Env,
+    msg: SubMsgResult,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
-    state.curr_executing_request_id = u64::MAX;
+    let mut executing = EXECUTING_REQUESTS.load(deps.storage)?;
+    if executing.is_empty() {
+        return Err(CommonError::Unauthorized {}.into());
+    }
+    let ExecutingRequest {
+        id,
+        mut request,
+        executor,
+    } = executing.remove(0);
+
+    let error = match msg {
+        SubMsgResult::Ok(_) => None,
+        SubMsgResult::Err(err) => Some(err),
+    };
+
+    // Keep failed request so that the owner can take the input asset back
+    if error.is_some() && !request.is_recurring {
+        request.status = RequestStatus::Failed;
+        requests().save(deps.storage, id, &request)?;
+        state.total_requests += 1;
+    }
+
+    REQUEST_EXECUTIONS.save(
+        deps.storage,
+        (id, request.executions_done),
+        &RequestExecution {
+            execution: request.executions_done,
+            executor,
+            executed_at: env.block.height,
+            success: error.is_none(),
+            error: error.clone(),
+        },
+    )?;
+
+    state.curr_executing_request_id = executing.first().map_or(u64::MAX, |next| next.id);
     STATE.save(deps.storage, &state)?;
-    Ok(Response::new().add_attributes(vec![attr("action", "finalize_execute")]))
+    if executing.is_empty() {
+        EXECUTING_REQUESTS.remove(deps.storage);
+    } else {
+        EXECUTING_REQUESTS.save(deps.storage, &executing)?;
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "finalize_execute"),
+        attr("id", id.to_string()),
+        attr("success", error.is_none().to_string()),
+    ]))
 }
 
 /// ## Description
@@ -1029,6 +2835,28 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             order_by,
         )?)?),
 
+        QueryMsg::RequestsByUser {
+            user,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_requests_by_user(
+            deps,
+            user,
+            start_after,
+            limit,
+        )?)?),
+
+        QueryMsg::RequestsByTarget {
+            target,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_requests_by_target(
+            deps,
+            target,
+            start_after,
+            limit,
+        )?)?),
+
         QueryMsg::State {} => Ok(to_binary(&query_state(deps)?)?),
 
         QueryMsg::EpochInfo {} => Ok(to_binary(&query_epoch_info(deps, env)?)?),
@@ -1037,9 +2865,28 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 
         QueryMsg::StakeAmount { user } => Ok(to_binary(&query_stake_amount(deps, user)?)?),
 
+        QueryMsg::Unbonding { user } => Ok(to_binary(&query_unbonding(deps, user)?)?),
+
+        QueryMsg::PendingRewards { user } => Ok(to_binary(&query_pending_rewards(deps, user)?)?),
+
+        QueryMsg::Treasury {} => Ok(to_binary(&query_treasury(deps)?)?),
+
+        QueryMsg::CurrentExecution {} => Ok(to_binary(&query_current_execution(deps)?)?),
+
         QueryMsg::Stakes { start, limit } => Ok(to_binary(&query_stakes(deps, start, limit)?)?),
 
         QueryMsg::Blacklist {} => Ok(to_binary(&query_blacklist(deps)?)?),
+
+        QueryMsg::RequestExecutions {
+            id,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_request_executions(
+            deps,
+            id,
+            start_after,
+            limit,
+        )?)?),
     }
 }
 
@@ -1061,13 +2908,22 @@ pub fn query_config(deps: Deps) -> StdResult<Config> {
 ///
 /// * **id** is the request id.
 pub fn query_request_info(deps: Deps, id: u64) -> StdResult<RequestInfoResponse> {
-    let info = REQUESTS.load(deps.storage, id).unwrap_or(Request {
+    let info = requests().load(deps.storage, id).unwrap_or(Request {
         user: zero_string(),
         target: zero_string(),
         msg: to_binary("")?,
-        input_asset: None,
+        input_assets: vec![],
         is_recurring: false,
         created_at: 0,
+        catch_failure: false,
+        status: RequestStatus::Pending,
+        valid_from: None,
+        expires_at: None,
+        recurring_schedule: None,
+        last_executed_at: None,
+        executions_done: 0,
+        fee: None,
+        pending_owner: None,
     });
     Ok(RequestInfoResponse { id, request: info })
 }
@@ -1078,7 +2934,7 @@ pub fn query_request_info(deps: Deps, id: u64) -> StdResult<RequestInfoResponse>
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **start_after** is an [`Option`] field which accepts an id of type [`u64`].
-/// This is the request from which we start to query.
+///   This is the request from which we start to query.
 ///
 /// * **limit** is a [`Option`] type. Sets the number of requests to be retrieved.
 ///
@@ -1110,6 +2966,83 @@ pub fn query_requests(
     })
 }
 
+/// ## Description
+/// Returns requests registered by `user` using [`RequestsResponse`]. Querying starts at `start_after` and returns `limit` requests.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **user** is the address of the user, which is an object of type [`String`].
+///
+/// * **start_after** is an [`Option`] field which accepts an id of type [`u64`].
+///   This is the request from which we start to query.
+///
+/// * **limit** is a [`Option`] type. Sets the number of requests to be retrieved.
+pub fn query_requests_by_user(
+    deps: Deps,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RequestsResponse> {
+    let user = deps.api.addr_validate(&user)?;
+    let requests = read_requests_by_user(deps.storage, user.to_string(), start_after, limit)?;
+
+    Ok(RequestsResponse {
+        requests: requests
+            .into_iter()
+            .map(|(id, request)| RequestInfoResponse { id, request })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns requests calling `target` using [`RequestsResponse`]. Querying starts at `start_after` and returns `limit` requests.
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **target** is the address of the target contract, which is an object of type [`String`].
+///
+/// * **start_after** is an [`Option`] field which accepts an id of type [`u64`].
+///   This is the request from which we start to query.
+///
+/// * **limit** is a [`Option`] type. Sets the number of requests to be retrieved.
+pub fn query_requests_by_target(
+    deps: Deps,
+    target: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RequestsResponse> {
+    let target = deps.api.addr_validate(&target)?;
+    let requests = read_requests_by_target(deps.storage, target.to_string(), start_after, limit)?;
+
+    Ok(RequestsResponse {
+        requests: requests
+            .into_iter()
+            .map(|(id, request)| RequestInfoResponse { id, request })
+            .collect(),
+    })
+}
+
+/// ## Description
+/// Returns the request being executed using [`CurrentExecutionResponse`], `None` when idle
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_current_execution(deps: Deps) -> StdResult<CurrentExecutionResponse> {
+    let executing = EXECUTING_REQUESTS
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    Ok(CurrentExecutionResponse {
+        execution: executing
+            .into_iter()
+            .next()
+            .map(|ExecutingRequest { id, request, .. }| CurrentExecution {
+                id,
+                user: request.user,
+                target: request.target,
+                input_assets: request.input_assets,
+            }),
+    })
+}
+
 /// ## Description
 /// Return current state of requests and stakes using [`StateResponse`]
 /// ## Params
@@ -1122,7 +3055,7 @@ pub fn query_state(deps: Deps) -> StdResult<StateResponse> {
         total_recurring_fee: state.total_recurring_fee,
         next_request_id: state.next_request_id,
         total_stake_amount: state.total_staked,
-        stakes_len: state.stakes.len() as u64,
+        num_stakers: state.num_stakers,
     };
 
     Ok(resp)
@@ -1178,27 +3111,82 @@ pub fn query_stake_amount(deps: Deps, user: String) -> StdResult<StakeAmountResp
 }
 
 /// ## Description
-/// Return stakings from `start` with limit of `limit` as [`StakesResponse`]
+/// Return unbonding queue of the user using [`UnbondingResponse`]
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 ///
-/// * **start** is starting id of the stakings.
+/// * **user** is a [`String`] which is the address of the user.
+pub fn query_unbonding(deps: Deps, user: String) -> StdResult<UnbondingResponse> {
+    let entries = UNBONDING
+        .may_load(deps.storage, &deps.api.addr_validate(&user)?)?
+        .unwrap_or_default();
+
+    Ok(UnbondingResponse { entries })
+}
+
+/// ## Description
+/// Return unclaimed execution fee rewards of the user using [`PendingRewardsResponse`]
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
 ///
-/// * **limit** is a [`u64`] of return size limit.
-pub fn query_stakes(deps: Deps, start: u64, limit: u64) -> StdResult<StakesResponse> {
-    let state = STATE.load(deps.storage)?;
+/// * **user** is a [`String`] which is the address of the user.
+pub fn query_pending_rewards(deps: Deps, user: String) -> StdResult<PendingRewardsResponse> {
+    let user = deps.api.addr_validate(&user)?;
+    let balance = STAKE_BALANCE.load(deps.storage, &user).unwrap_or_default();
+
+    let mut rewards = vec![];
+    for index in REWARD_INDEXES.range(deps.storage, None, None, Order::Ascending) {
+        let (denom, index) = index?;
+        let staker_rewards = STAKER_REWARDS
+            .may_load(deps.storage, (&user, &denom))?
+            .unwrap_or_default();
+        let amount = staker_rewards.pending
+            + balance * (index.reward_per_stake - staker_rewards.reward_per_stake);
+        if !amount.is_zero() {
+            rewards.push(Coin { denom, amount });
+        }
+    }
 
-    let mut end = (start + limit) as usize;
-    if end > state.stakes.len() {
-        end = state.stakes.len()
-    };
-    let start = start as usize;
+    Ok(PendingRewardsResponse { rewards })
+}
+
+/// ## Description
+/// Return protocol fees of the treasury using [`TreasuryResponse`]
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_treasury(deps: Deps) -> StdResult<TreasuryResponse> {
+    let config = CONFIG.load(deps.storage)?;
 
-    Ok(StakesResponse {
-        stakes: state.stakes[start..end].to_vec(),
+    Ok(TreasuryResponse {
+        treasury: config.treasury.map(|treasury| treasury.to_string()),
+        balance: _treasury_coins(deps, TREASURY_BALANCE)?,
+        withdrawn: _treasury_coins(deps, TREASURY_WITHDRAWN)?,
     })
 }
 
+/// ## Description
+/// Return stakers from `start` with limit of `limit` as [`StakesResponse`]
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **start** is starting slot of the stakers, from 0.
+///
+/// * **limit** is a [`u64`] of return size limit.
+pub fn query_stakes(deps: Deps, start: u64, limit: u64) -> StdResult<StakesResponse> {
+    let stakes = SLOT_STAKERS
+        .range(
+            deps.storage,
+            Some(Bound::inclusive(start + 1)),
+            None,
+            OrderBy::Asc.into(),
+        )
+        .take(limit as usize)
+        .map(|item| item.map(|(_, staker)| staker.to_string()))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(StakesResponse { stakes })
+}
+
 /// ## Description
 /// Return blacklist as [`BlacklistResponse`]
 /// ## Params
@@ -1215,3 +3203,25 @@ pub fn query_blacklist(deps: Deps) -> StdResult<BlacklistResponse> {
         blacklist: blacklist?,
     })
 }
+
+/// ## Description
+/// Return stored execution outcomes of the request with `id` as [`RequestExecutionsResponse`]
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **id** is the request id.
+///
+/// * **start_after** is an [`Option`] field which accepts a block height of type [`u64`].
+///
+/// * **limit** is a [`Option`] type. Sets the number of outcomes to be retrieved.
+pub fn query_request_executions(
+    deps: Deps,
+    id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<RequestExecutionsResponse> {
+    Ok(RequestExecutionsResponse {
+        id,
+        executions: read_request_executions(deps.storage, id, start_after, limit)?,
+    })
+}
@@ -64,11 +64,38 @@ pub enum ContractError {
     #[error("Stakes Exceed Cap")]
     StakesExceedCap {},
 
-    #[error("Idx not yours")]
-    IdxNotYou {},
+    #[error("Insufficient stake")]
+    InsufficientStake {},
 
-    #[error("Idx is out of bound")]
-    IdxOutOfBound {},
+    #[error("No unbonded stake to withdraw")]
+    NoUnbondedStake {},
+
+    #[error("Epoch not missed")]
+    EpochNotMissed {},
+
+    #[error("Nothing to slash")]
+    NothingToSlash {},
+
+    #[error("No rewards to claim")]
+    NoRewards {},
+
+    #[error("Treasury not set")]
+    TreasuryNotSet {},
+
+    #[error("No protocol fee to withdraw")]
+    NoProtocolFee {},
+
+    #[error("Duplicate input assets")]
+    DuplicateInputAssets {},
+
+    #[error("Duplicate request ids")]
+    DuplicateRequestIds {},
+
+    #[error("No pending transfer")]
+    NoPendingTransfer {},
+
+    #[error("Input asset amount can't be zero")]
+    ZeroInputAsset {},
 
     #[error("Invalid recurring count")]
     InvalidRecurringCount {},
@@ -78,6 +105,24 @@ pub enum ContractError {
 
     #[error("Target blacklisted")]
     TargetBlacklisted {},
+
+    #[error("Request failed, it can only be cancelled")]
+    RequestFailed {},
+
+    #[error("Request is not valid yet")]
+    RequestNotValidYet {},
+
+    #[error("Request expired")]
+    RequestExpired {},
+
+    #[error("Invalid expiration")]
+    InvalidExpiration {},
+
+    #[error("Only recurring requests can have a schedule")]
+    ScheduleForNonRecurring {},
+
+    #[error("Request is not due yet")]
+    RequestNotDue {},
 }
 
 impl From<semver::Error> for ContractError {
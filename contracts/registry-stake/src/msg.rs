@@ -2,15 +2,20 @@ use autonomy::{
     asset::{Asset, AssetInfo},
     types::OrderBy,
 };
-use cosmwasm_std::{Binary, Uint128};
+use cosmwasm_std::{Binary, Coin, Uint128};
 use cw20::Cw20ReceiveMsg;
+use cw_utils::{Duration, Expiration, Scheduled};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::Request;
+pub use autonomy::registry::{CurrentExecution, CurrentExecutionResponse};
+
+use crate::state::{
+    RandomnessSource, RecurringSchedule, Request, RequestExecution, UnbondingEntry,
+};
 
 /// Config struct to initialze or update configuration
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CreateOrUpdateConfig {
     /// Contract admin
     pub admin: Option<String>,
@@ -29,9 +34,55 @@ pub struct CreateOrUpdateConfig {
 
     /// Blocks in a single epoch
     pub blocks_in_epoch: Option<u64>,
+
+    /// Share of the escrowed fee paid to whoever cancels an expired request, in bps
+    pub expiry_bounty_bps: Option<u64>,
+
+    /// Blocks after the epoch start from which any staker can execute requests
+    pub staker_grace_blocks: Option<u64>,
+
+    /// Blocks after the epoch start from which anyone can execute requests
+    pub open_grace_blocks: Option<u64>,
+
+    /// Unsets the grace blocks before setting the given ones, ignored on instantiation
+    pub clear_grace_blocks: Option<bool>,
+
+    /// Source of the randomness for executor selection
+    pub randomness_source: Option<RandomnessSource>,
+
+    /// Period for unstaked AUTO to be withdrawn
+    pub unbonding_period: Option<Duration>,
+
+    /// Unsets the unbonding period before setting the given one, ignored on instantiation
+    pub clear_unbonding_period: Option<bool>,
+
+    /// Share of the stake balance slashed from the executor missing its epoch, in bps
+    pub slash_bps: Option<u64>,
+
+    /// Blocks that should remain in the epoch after requests became pending,
+    /// for its executor to be slashed
+    pub slash_notice_blocks: Option<u64>,
+
+    /// Share of the slashed AUTO paid to the reporter, in bps
+    pub slash_bounty_bps: Option<u64>,
+
+    /// Receiver of slashed AUTO and protocol fees
+    pub treasury: Option<String>,
+
+    /// Unsets the treasury before setting the given one, ignored on instantiation
+    pub clear_treasury: Option<bool>,
+
+    /// Share of the execution fee distributed to stakers, in bps
+    pub staker_fee_bps: Option<u64>,
+
+    /// Share of the execution fee accrued to the treasury, in bps
+    pub protocol_fee_bps: Option<u64>,
+
+    /// CW20 tokens allowed to create requests by the `CreateRequest` hook, replacing the current ones
+    pub hook_tokens: Option<Vec<String>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub config: CreateOrUpdateConfig,
 }
@@ -39,7 +90,7 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct MigrateMsg {}
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CreateRequestInfo {
     /// Target contract to call for this request
     pub target: String,
@@ -47,11 +98,41 @@ pub struct CreateRequestInfo {
     /// Msg for the target contract
     pub msg: Binary,
 
-    /// Assets used for this call
+    /// Asset used for this call, kept for compatibility and prepended to `input_assets`
+    #[serde(default)]
     pub input_asset: Option<Asset>,
 
+    /// Assets used for this call
+    #[serde(default)]
+    pub input_assets: Vec<Asset>,
+
     /// Is this recurring request?
     pub is_recurring: bool,
+
+    /// Record target failures instead of reverting the execution.
+    /// Executor is still paid and a non-recurring request becomes `Failed`
+    pub catch_failure: Option<bool>,
+
+    /// Request can't be executed before this point
+    pub valid_from: Option<Scheduled>,
+
+    /// Request can't be executed from this point, and anyone can cancel it
+    pub expires_at: Option<Expiration>,
+
+    /// Execution schedule, only for recurring requests
+    pub recurring_schedule: Option<RecurringSchedule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpdateRequestInfo {
+    /// New msg for the target contract
+    pub msg: Option<Binary>,
+
+    /// New assets used for this call, replacing the escrowed ones
+    pub input_assets: Option<Vec<Asset>>,
+
+    /// New execution schedule, only for recurring requests
+    pub recurring_schedule: Option<RecurringSchedule>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -66,10 +147,31 @@ pub enum ExecuteMsg {
 
     /// Create a new execution request
     CreateRequest { request_info: CreateRequestInfo },
-    /// Cancel a request with `id`
+    /// Update the pending request with `id`, only by its owner
+    UpdateRequest {
+        id: u64,
+        request_info: UpdateRequestInfo,
+    },
+    /// Transfer the request with `id` to `new_owner`, who accepts it first if `require_accept`.
+    /// Recurring requests always wait for the acceptance, as the new owner pays their fees
+    TransferRequest {
+        id: u64,
+        new_owner: String,
+        require_accept: Option<bool>,
+    },
+    /// Accept the pending transfer of the request with `id`
+    AcceptRequestTransfer { id: u64 },
+    /// Cancel the pending transfer of the request with `id`, only by its owner
+    CancelRequestTransfer { id: u64 },
+    /// Cancel a request with `id`, anyone can cancel an expired request
     CancelRequest { id: u64 },
     /// Execute a request with `id`
     ExecuteRequest { id: u64 },
+    /// Execute many requests with `ids`, skipping the ones failing validation if `skip_failed`
+    ExecuteRequests { ids: Vec<u64>, skip_failed: bool },
+    /// Forward the input assets of the request being executed along with its call,
+    /// only by the registry itself for CW20 assets combined with other assets
+    ForwardInputAssets {},
     /// Deposit into recurring fee pool
     DepositRecurringFee { recurring_count: u64 },
     /// Withdraw from recurring fee pool
@@ -82,8 +184,18 @@ pub enum ExecuteMsg {
     /// Staking when execution fee is native asset
     /// `num_stakes` is the number of staking
     StakeDenom { num_stakes: u64 },
-    /// Unstake stakers of the caller at index array of `idxs`
-    Unstake { idxs: Vec<u64> },
+    /// Unstake `num_stakes` stakings of the caller, moving them into the unbonding queue
+    Unstake { num_stakes: u64 },
+    /// Withdraw unstaked AUTO of the caller after the unbonding period
+    WithdrawUnbonded {},
+    /// Record the request with `id` can be executed during the current epoch
+    ReportPendingRequest { id: u64 },
+    /// Slash the executor who executed nothing during the `epoch` while requests were pending
+    ReportMissedEpoch { epoch: u64 },
+    /// Claim execution fee rewards of the caller
+    ClaimRewards {},
+    /// Withdraw accrued protocol fees to the treasury
+    WithdrawTreasury {},
     /// Update executor for current epoch
     UpdateExecutor {},
 
@@ -95,11 +207,14 @@ pub enum ExecuteMsg {
     RemoveFromBlacklist { addrs: Vec<String> }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     /// Stake AUTO to be an executor
     Stake { num_stakes: u64 },
+    /// Create a new request with the received token as the input asset,
+    /// the execution fee is paid from the recurring fee balance of the sender
+    CreateRequest { request_info: CreateRequestInfo },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
@@ -123,14 +238,40 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    /// Get many requests registered by a user
+    RequestsByUser {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Get many requests calling a target contract
+    RequestsByTarget {
+        target: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Get current executor rotation epoch info
     EpochInfo {},
     /// Get staked amount of a user
     StakeAmount { user: String },
-    /// Get array of staked addresses
+    /// Get unbonding queue of a user
+    Unbonding { user: String },
+    /// Get unclaimed execution fee rewards of a user
+    PendingRewards { user: String },
+    /// Get protocol fees of the treasury
+    Treasury {},
+    /// Get the request being executed, for its target to authenticate the user
+    CurrentExecution {},
+    /// Get array of stakers, from the slot `start`
     Stakes { start: u64, limit: u64 },
     /// Get array of blacklisted addresses
     Blacklist { },
+    /// Get stored execution outcomes of a request, after the execution number `start_after`
+    RequestExecutions {
+        id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 /// Response for query registry state
@@ -151,23 +292,30 @@ pub struct StateResponse {
     /// Total amount of staked AUTO
     pub total_stake_amount: Uint128,
 
-    /// Lenght of stakes array
-    pub stakes_len: u64,
+    /// Number of stakers
+    pub num_stakers: u64,
 }
 
 /// Response for single request query
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RequestInfoResponse {
     pub id: u64,
     pub request: Request,
 }
 
 /// Response for query many requests
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RequestsResponse {
     pub requests: Vec<RequestInfoResponse>,
 }
 
+/// Response for execution outcomes of a request
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RequestExecutionsResponse {
+    pub id: u64,
+    pub executions: Vec<RequestExecution>,
+}
+
 /// Response for current epoch info
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 pub struct EpochInfoResponse {
@@ -193,6 +341,27 @@ pub struct StakeAmountResponse {
     pub amount: Uint128,
 }
 
+/// Response for unclaimed execution fee rewards of a user, in every denom
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct PendingRewardsResponse {
+    pub rewards: Vec<Coin>,
+}
+
+/// Response for protocol fees of the treasury in every denom, `balance` is not withdrawn yet
+/// and `withdrawn` is already sent, so the accrued fees are the sum of both
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct TreasuryResponse {
+    pub treasury: Option<String>,
+    pub balance: Vec<Coin>,
+    pub withdrawn: Vec<Coin>,
+}
+
+/// Response for unbonding queue of a user
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingResponse {
+    pub entries: Vec<UnbondingEntry>,
+}
+
 /// Response for staked list
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 pub struct StakesResponse {
@@ -2,17 +2,18 @@ use autonomy::{
     asset::{Asset, AssetInfo},
     types::OrderBy,
 };
-use cw_storage_plus::{Bound, Item, Map};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Binary, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, StdResult, Storage, Uint128};
 
 use cw_controllers::Admin;
+use cw_utils::{Duration, Expiration, Scheduled};
 
 /// ## Description
 /// Protocol configuration
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     /// Amount of request execution fee
     pub fee_amount: Uint128,
@@ -24,6 +25,60 @@ pub struct Config {
     pub stake_amount: Uint128,
     /// Blocks in a single epoch
     pub blocks_in_epoch: u64,
+    /// Share of the escrowed fee paid to whoever cancels an expired request, in bps
+    #[serde(default)]
+    pub expiry_bounty_bps: u64,
+    /// Blocks after the epoch start from which any staker can execute requests.
+    /// Only the epoch executor can execute if not set
+    #[serde(default)]
+    pub staker_grace_blocks: Option<u64>,
+    /// Blocks after the epoch start from which anyone can execute requests.
+    /// Only the epoch executor, or stakers after `staker_grace_blocks`, can execute if not set
+    #[serde(default)]
+    pub open_grace_blocks: Option<u64>,
+    /// Source of the randomness for executor selection
+    #[serde(default)]
+    pub randomness_source: RandomnessSource,
+    /// Period for unstaked AUTO to be withdrawn, unstaking returns AUTO instantly if not set
+    #[serde(default)]
+    pub unbonding_period: Option<Duration>,
+    /// Share of the stake balance slashed from the executor missing its epoch, in bps
+    #[serde(default)]
+    pub slash_bps: u64,
+    /// Blocks that should remain in the epoch after requests became pending,
+    /// for its executor to be slashed
+    #[serde(default)]
+    pub slash_notice_blocks: u64,
+    /// Share of the slashed AUTO paid to the reporter, in bps.
+    /// Only this share is slashed if the treasury is not set
+    #[serde(default)]
+    pub slash_bounty_bps: u64,
+    /// Receiver of slashed AUTO and protocol fees
+    #[serde(default)]
+    pub treasury: Option<Addr>,
+    /// Share of the execution fee distributed to stakers, in bps
+    #[serde(default)]
+    pub staker_fee_bps: u64,
+    /// Share of the execution fee accrued to the treasury, in bps
+    #[serde(default)]
+    pub protocol_fee_bps: u64,
+    /// CW20 tokens allowed to create requests by the `CreateRequest` hook,
+    /// which charges the fee from the recurring fee balance of the reported sender
+    #[serde(default)]
+    pub hook_tokens: Vec<Addr>,
+}
+
+/// ## Description
+/// Source of the randomness for executor selection
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RandomnessSource {
+    /// Seeded by the block height only, anyone can compute future executors
+    #[default]
+    BlockHeight,
+    /// Seeded by the hash chain in [`State`], which mixes in the block time,
+    /// the contract address and the sender of every registry and staking call
+    HashChain,
 }
 
 /// ## Description
@@ -45,8 +100,12 @@ pub struct State {
 
     /// Total amount of staked AUTO
     pub total_staked: Uint128,
-    /// Address list of all stakers
-    pub stakes: Vec<String>,
+    /// Number of stakers, who occupy slots `1..=num_stakers` of [`STAKE_TREE`]
+    #[serde(default)]
+    pub num_stakers: u64,
+    /// Running hash chain for executor selection
+    #[serde(default)]
+    pub entropy: Binary,
     /// Last epoch for executor rotation
     pub last_epoch: u64,
     /// Address of executor in the last epoch
@@ -54,8 +113,50 @@ pub struct State {
 }
 
 /// ## Description
-/// Actual request struct
+/// Execution fee rewards distributed to stakers in a denom
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct RewardIndex {
+    /// Accumulated rewards per staked AUTO
+    pub reward_per_stake: Decimal,
+    /// Rewards left over by the rounding of `reward_per_stake`, added to the next distribution
+    pub remainder: Uint128,
+}
+
+/// ## Description
+/// Execution fee rewards of a staker in a denom
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct StakerRewards {
+    /// `reward_per_stake` of the denom when the rewards were settled last
+    pub reward_per_stake: Decimal,
+    /// Rewards settled but not claimed yet
+    pub pending: Uint128,
+}
+
+/// ## Description
+/// Execution activity of an epoch
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct EpochActivity {
+    /// Executor chosen for the epoch
+    pub executor: Addr,
+    /// Height at which requests first became pending during the epoch
+    pub pending_since: Option<u64>,
+    /// Whether the executor executed any request during the epoch
+    pub executed: bool,
+}
+
+/// ## Description
+/// Unstaked AUTO in the unbonding queue
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingEntry {
+    /// Amount of unstaked AUTO
+    pub amount: Uint128,
+    /// AUTO can be withdrawn from this point
+    pub release_at: Expiration,
+}
+
+/// ## Description
+/// Actual request struct
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Request {
     /// The user who registered this request.
     pub user: String,
@@ -63,12 +164,88 @@ pub struct Request {
     pub target: String,
     /// Msg to call the target
     pub msg: Binary,
-    /// Asset sent in advance
-    pub input_asset: Option<Asset>,
+    /// Assets sent in advance
+    #[serde(default)]
+    pub input_assets: Vec<Asset>,
     /// Recurring request
     pub is_recurring: bool,
     /// Timestamp for creation
     pub created_at: u64,
+    /// Record target failures instead of reverting the execution
+    #[serde(default)]
+    pub catch_failure: bool,
+    /// Current status of the request
+    #[serde(default)]
+    pub status: RequestStatus,
+    /// Request can't be executed before this point
+    pub valid_from: Option<Scheduled>,
+    /// Request can't be executed from this point, and anyone can cancel it
+    pub expires_at: Option<Expiration>,
+    /// Schedule of recurring request executions
+    #[serde(default)]
+    pub recurring_schedule: Option<RecurringSchedule>,
+    /// Point of the last execution
+    #[serde(default)]
+    pub last_executed_at: Option<Scheduled>,
+    /// Number of executions done
+    #[serde(default)]
+    pub executions_done: u64,
+    /// Execution fee escrowed at the creation, recurring requests pay from the recurring fee pool
+    #[serde(default)]
+    pub fee: Option<Asset>,
+    /// Owner the request is being transferred to, waiting for the acceptance
+    #[serde(default)]
+    pub pending_owner: Option<String>,
+}
+
+/// ## Description
+/// Schedule of recurring request executions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecurringSchedule {
+    /// Minimum interval between executions, in blocks or seconds
+    pub interval: Duration,
+    /// Request is removed after this number of executions, unlimited if not set
+    pub max_executions: Option<u64>,
+}
+
+/// ## Description
+/// Status of a request in the queue
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestStatus {
+    /// Waiting for the execution
+    #[default]
+    Pending,
+    /// Target failed on execution, only the owner can cancel it to get input asset back
+    Failed,
+}
+
+/// ## Description
+/// Request being executed, stored until the reply of the target call
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExecutingRequest {
+    /// Id of the request
+    pub id: u64,
+    /// Request at the moment of the execution
+    pub request: Request,
+    /// Address of the executor
+    pub executor: String,
+}
+
+/// ## Description
+/// Outcome of a single request execution
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RequestExecution {
+    /// Number of the execution, starting from 1
+    pub execution: u64,
+    /// Address of the executor
+    pub executor: String,
+    /// Block height of the execution
+    pub executed_at: u64,
+    /// Did the target call succeed?
+    pub success: bool,
+    /// Reason of the target failure
+    pub error: Option<String>,
 }
 
 /// Admin of the contract who can update config or set new admin
@@ -83,17 +260,133 @@ pub const STATE: Item<State> = Item::new("state");
 
 /// Stores staked AUTO balance of users
 pub const STAKE_BALANCE: Map<&Addr, Uint128> = Map::new("stake_balance");
+/// Capacity of staker slots in [`STAKE_TREE`], should be a power of two
+pub const MAX_STAKERS: u64 = 1 << 16;
+/// Slot of each staker in [`STAKE_TREE`]
+pub const STAKER_SLOTS: Map<&Addr, u64> = Map::new("staker_slots");
+/// Staker occupying each slot of [`STAKE_TREE`]
+pub const SLOT_STAKERS: Map<u64, Addr> = Map::new("slot_stakers");
+/// Fenwick tree of stake balances over staker slots, for stake-weighted executor selection
+pub const STAKE_TREE: Map<u64, Uint128> = Map::new("stake_tree");
+/// Stores protocol fees not withdrawn to the treasury per denom
+pub const TREASURY_BALANCE: Map<&str, Uint128> = Map::new("treasury_balance");
+/// Stores protocol fees withdrawn to the treasury per denom
+pub const TREASURY_WITHDRAWN: Map<&str, Uint128> = Map::new("treasury_withdrawn");
+/// Stores execution fee rewards distributed to stakers per denom
+pub const REWARD_INDEXES: Map<&str, RewardIndex> = Map::new("reward_indexes");
+/// Stores execution fee rewards of stakers per denom
+pub const STAKER_REWARDS: Map<(&Addr, &str), StakerRewards> = Map::new("staker_rewards");
+/// Stores execution activity of the epochs
+pub const EPOCH_ACTIVITY: Map<u64, EpochActivity> = Map::new("epoch_activity");
+/// Stores unstaked AUTO of users waiting for the unbonding period
+pub const UNBONDING: Map<&Addr, Vec<UnbondingEntry>> = Map::new("unbonding");
 /// Stores recurring requests fee balance of users
 pub const RECURRING_BALANCE: Map<&Addr, Uint128> = Map::new("recurring_balance");
 /// Blacklist of target addresses
 pub const BLACKLIST: Map<&Addr, String> = Map::new("blacklist");
-/// Stroes all requests
-pub const REQUESTS: Map<u64, Request> = Map::new("requests");
+/// ## Description
+/// Secondary indexes of [`requests`]
+pub struct RequestIndexes<'a> {
+    /// Requests by the user who registered them
+    pub user: MultiIndex<'a, String, Request, u64>,
+    /// Requests by target contract
+    pub target: MultiIndex<'a, String, Request, u64>,
+}
+
+impl<'a> IndexList<Request> for RequestIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Request>> + '_> {
+        let v: Vec<&dyn Index<Request>> = vec![&self.user, &self.target];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Stores all requests, indexed by user and target
+pub fn requests<'a>() -> IndexedMap<'a, u64, Request, RequestIndexes<'a>> {
+    let indexes = RequestIndexes {
+        user: MultiIndex::new(|_pk, r| r.user.clone(), "requests", "requests__user"),
+        target: MultiIndex::new(|_pk, r| r.target.clone(), "requests", "requests__target"),
+    };
+    IndexedMap::new("requests", indexes)
+}
+/// Requests being executed in order, each until its reply is processed
+pub const EXECUTING_REQUESTS: Item<Vec<ExecutingRequest>> = Item::new("executing_requests");
+/// Execution outcomes of requests, keyed by request id and execution number
+pub const REQUEST_EXECUTIONS: Map<(u64, u64), RequestExecution> = Map::new("request_executions");
+
+/// ## Description
+/// Stakes stored in [`STATE`] before 0.3.0, one address per stake unit.
+/// Only read by the migration
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct LegacyStakes {
+    #[serde(default)]
+    pub stakes: Vec<String>,
+}
+
+/// Legacy view of [`STATE`]
+pub const LEGACY_STAKES: Item<LegacyStakes> = Item::new("state");
+
+/// Input asset of requests stored before 0.5.0.
+/// Only read by the migration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyRequest {
+    #[serde(default)]
+    pub input_asset: Option<Asset>,
+}
+
+/// Legacy view of the requests
+pub const LEGACY_REQUESTS: Map<u64, LegacyRequest> = Map::new("requests");
+
+/// ## Description
+/// Replaces the balance of the `slot` in [`STAKE_TREE`] from `old` to `new`.
+/// ## Params
+/// `slot` is the staker slot, starting from 1.
+///
+/// `old` is the current balance of the slot.
+///
+/// `new` is the new balance of the slot.
+pub fn update_stake_tree(
+    storage: &mut dyn Storage,
+    slot: u64,
+    old: Uint128,
+    new: Uint128,
+) -> StdResult<()> {
+    let mut idx = slot;
+    while idx <= MAX_STAKERS {
+        let node = STAKE_TREE.may_load(storage, idx)?.unwrap_or_default();
+        STAKE_TREE.save(storage, idx, &(node + new - old))?;
+        idx += idx & idx.wrapping_neg();
+    }
+    Ok(())
+}
+
+/// ## Description
+/// Finds the slot in [`STAKE_TREE`] whose cumulative balance range contains `point`,
+/// so that each slot is found with the probability proportional to its balance.
+/// ## Params
+/// `point` should be less than the sum of all balances.
+pub fn find_stake_slot(storage: &dyn Storage, point: Uint128) -> StdResult<u64> {
+    let mut slot = 0;
+    let mut rest = point;
+    let mut step = MAX_STAKERS;
+    while step > 0 {
+        if slot + step <= MAX_STAKERS {
+            let node = STAKE_TREE
+                .may_load(storage, slot + step)?
+                .unwrap_or_default();
+            if node <= rest {
+                slot += step;
+                rest -= node;
+            }
+        }
+        step >>= 1;
+    }
+    Ok(slot + 1)
+}
 
 /// ## Pagination settings
-/// The maximum limit for reading requests from [`REQUESTS`]
+/// The maximum limit for reading requests from [`requests`]
 const MAX_LIMIT: u32 = 30;
-/// The default limit for reading requests from [`REQUESTS`]
+/// The default limit for reading requests from [`requests`]
 const DEFAULT_LIMIT: u32 = 10;
 
 /// ## Description
@@ -112,7 +405,7 @@ pub fn read_requests(
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
 
-    REQUESTS
+    requests()
         .range(
             storage,
             start,
@@ -122,3 +415,80 @@ pub fn read_requests(
         .take(limit)
         .collect()
 }
+
+/// ## Description
+/// Reads requests registered by `user` using the user index of [`requests`].
+/// ## Params
+/// `user` is the address of the user.
+///
+/// `start_after` is the request id from which the function starts to fetch results. It is an [`Option`].
+///
+/// `limit` is the number of items to retreive. It is an [`Option`].
+pub fn read_requests_by_user(
+    storage: &dyn Storage,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Request)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    requests()
+        .idx
+        .user
+        .prefix(user)
+        .range(storage, start, None, OrderBy::Asc.into())
+        .take(limit)
+        .collect()
+}
+
+/// ## Description
+/// Reads requests calling `target` using the target index of [`requests`].
+/// ## Params
+/// `target` is the address of the target contract.
+///
+/// `start_after` is the request id from which the function starts to fetch results. It is an [`Option`].
+///
+/// `limit` is the number of items to retreive. It is an [`Option`].
+pub fn read_requests_by_target(
+    storage: &dyn Storage,
+    target: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, Request)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    requests()
+        .idx
+        .target
+        .prefix(target)
+        .range(storage, start, None, OrderBy::Asc.into())
+        .take(limit)
+        .collect()
+}
+
+/// ## Description
+/// Reads execution outcomes of the request with `id` from [`REQUEST_EXECUTIONS`].
+/// ## Params
+/// `id` is the request id.
+///
+/// `start_after` is the execution number from which the function starts to fetch results. It is an [`Option`].
+///
+/// `limit` is the number of items to retreive. It is an [`Option`].
+pub fn read_request_executions(
+    storage: &dyn Storage,
+    id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<RequestExecution>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    REQUEST_EXECUTIONS
+        .prefix(id)
+        .range(storage, start, None, OrderBy::Asc.into())
+        .take(limit)
+        .map(|item| item.map(|(_, execution)| execution))
+        .collect()
+}
@@ -3,20 +3,8 @@ use cosmwasm_std::{
     from_slice, Coin, Empty, OwnedDeps,
     Querier, QuerierResult, QueryRequest, SystemError, SystemResult
 };
-use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use autonomy::asset::{Asset, AssetInfo};
-
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum QueryMsg {
-    Pair { asset_infos: [AssetInfo; 2] },
-    Simulation { offer_asset: Asset },
-    EpochInfo {},
-}
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies.
 /// This uses the Astroport CustomQuerier.
@@ -54,12 +42,6 @@ impl Querier for WasmMockQuerier {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "snake_case")]
-pub enum MockQueryMsg {
-    Price {},
-}
-
 impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         self.base.handle_query(request)
@@ -76,7 +58,7 @@ impl WasmMockQuerier {
 
     pub fn with_balance(&mut self, balances: &[(&String, &[Coin])]) {
         for (addr, balance) in balances {
-            self.base.update_balance(&(*addr).clone(), balance.to_vec());
+            self.base.update_balance((*addr).clone(), balance.to_vec());
         }
     }
 
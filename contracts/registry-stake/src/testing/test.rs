@@ -6,18 +6,66 @@ use crate::msg::{
     BlacklistResponse, CreateOrUpdateConfig, CreateRequestInfo, Cw20HookMsg, EpochInfoResponse,
     ExecuteMsg, InstantiateMsg, QueryMsg, RecurringFeeAmountResponse, RequestInfoResponse,
     RequestsResponse, StakeAmountResponse, StakesResponse, StateResponse, MigrateMsg,
+    RequestExecutionsResponse, UnbondingResponse, PendingRewardsResponse, TreasuryResponse, UpdateRequestInfo,
+    CurrentExecution, CurrentExecutionResponse,
 };
-use crate::state::{Config, Request};
-use crate::testing::mock_querier::mock_dependencies;
+use crate::state::{Config, RandomnessSource, REWARD_INDEXES, RecurringSchedule, Request, RequestExecution, RequestStatus, UnbondingEntry};
+use crate::testing::mock_querier::{mock_dependencies, WasmMockQuerier};
 
 use autonomy::asset::{Asset, AssetInfo};
 use autonomy::error::CommonError;
-use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Coin, CosmosMsg, SubMsg, Timestamp, Uint128, WasmMsg, ReplyOn, BankMsg, Reply, SubMsgResult, SubMsgResponse, Response,
+    attr, from_binary, to_binary, Addr, Binary, Coin, Deps, OwnedDeps, CosmosMsg, SubMsg, Timestamp, Uint128, WasmMsg, ReplyOn, BankMsg, Reply, SubMsgResult, SubMsgResponse, Response,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw2::set_contract_version;
 use cw_controllers::{AdminError, AdminResponse};
+use cw_storage_plus::Map;
+use cw_utils::{Duration, Expiration, Scheduled};
+
+/// Config with the common test values, tests override the fields they care about
+fn default_config() -> CreateOrUpdateConfig {
+    CreateOrUpdateConfig {
+        admin: Some("admin".to_string()),
+        fee_amount: Some(Uint128::from(10000u128)),
+        fee_denom: Some("utest".to_string()),
+        auto: Some(AssetInfo::NativeToken {
+            denom: "uauto".to_string(),
+        }),
+        stake_amount: Some(Uint128::from(1000u128)),
+        blocks_in_epoch: Some(100),
+        expiry_bounty_bps: None,
+        staker_grace_blocks: None,
+        open_grace_blocks: None,
+        clear_grace_blocks: None,
+        randomness_source: None,
+        unbonding_period: None,
+        clear_unbonding_period: None,
+        slash_bps: None,
+        slash_notice_blocks: None,
+        slash_bounty_bps: None,
+        treasury: None,
+        clear_treasury: None,
+        staker_fee_bps: None,
+        protocol_fee_bps: None,
+        hook_tokens: None,
+    }
+}
+
+fn default_request_info() -> CreateRequestInfo {
+    CreateRequestInfo {
+        target: "contract0000".to_string(),
+        msg: to_binary("").unwrap(),
+        input_asset: None,
+        input_assets: vec![],
+        is_recurring: false,
+        catch_failure: None,
+        valid_from: None,
+        expires_at: None,
+        recurring_schedule: None,
+    }
+}
 
 #[test]
 fn proper_initialization_migrate() {
@@ -27,13 +75,11 @@ fn proper_initialization_migrate() {
 
     let mut config = CreateOrUpdateConfig {
         admin: None,
-        fee_amount: Some(Uint128::from(10000u128)),
-        fee_denom: Some("utest".to_string()),
         auto: Some(AssetInfo::Token {
             contract_addr: Addr::unchecked("auto"),
         }),
-        stake_amount: Some(Uint128::from(1000u128)),
         blocks_in_epoch: Some(1),
+        ..default_config()
     };
     assert_eq!(
         instantiate(
@@ -76,7 +122,19 @@ fn proper_initialization_migrate() {
             fee_denom: config.fee_denom.unwrap(),
             auto: config.auto.unwrap(),
             stake_amount: config.stake_amount.unwrap(),
-            blocks_in_epoch: config.blocks_in_epoch.unwrap()
+            blocks_in_epoch: config.blocks_in_epoch.unwrap(),
+            expiry_bounty_bps: 0,
+            staker_grace_blocks: None,
+            open_grace_blocks: None,
+            randomness_source: RandomnessSource::BlockHeight,
+            unbonding_period: None,
+            slash_bps: 0,
+            slash_notice_blocks: 0,
+            slash_bounty_bps: 0,
+            treasury: None,
+            staker_fee_bps: 0,
+            protocol_fee_bps: 0,
+            hook_tokens: vec![],
         }
     );
 
@@ -91,7 +149,7 @@ fn proper_initialization_migrate() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 0,
             total_stake_amount: Uint128::zero(),
-            stakes_len: 0
+            num_stakers: 0
         }
     );
 
@@ -109,13 +167,11 @@ fn test_claim_admin() {
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
             admin: Some(admin.to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
             auto: Some(AssetInfo::Token {
                 contract_addr: Addr::unchecked("auto"),
             }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -131,6 +187,7 @@ fn test_claim_admin() {
             auto: None,
             stake_amount: None,
             blocks_in_epoch: None,
+            ..default_config()
         },
     };
     let info = mock_info("admin", &[]);
@@ -197,13 +254,11 @@ fn test_update_config() {
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
             admin: Some(admin.to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
             auto: Some(AssetInfo::Token {
                 contract_addr: Addr::unchecked("auto"),
             }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -219,6 +274,7 @@ fn test_update_config() {
             auto: None,
             stake_amount: Some(Uint128::from(2000u128)),
             blocks_in_epoch: None,
+            ..default_config()
         },
     };
     // With wrong admin
@@ -238,6 +294,8 @@ fn test_update_config() {
         auto: None,
         stake_amount: None,
         blocks_in_epoch: Some(12),
+        slash_notice_blocks: Some(6),
+        ..default_config()
     };
     let msg = ExecuteMsg::UpdateConfig {
         config: new_config.clone(),
@@ -255,7 +313,19 @@ fn test_update_config() {
             fee_denom: new_config.fee_denom.unwrap(),
             auto: config.auto.clone(),
             stake_amount: config.stake_amount,
-            blocks_in_epoch: new_config.blocks_in_epoch.unwrap()
+            blocks_in_epoch: new_config.blocks_in_epoch.unwrap(),
+            expiry_bounty_bps: 0,
+            staker_grace_blocks: None,
+            open_grace_blocks: None,
+            randomness_source: RandomnessSource::BlockHeight,
+            unbonding_period: None,
+            slash_bps: 0,
+            slash_notice_blocks: new_config.slash_notice_blocks.unwrap(),
+            slash_bounty_bps: 0,
+            treasury: None,
+            staker_fee_bps: 0,
+            protocol_fee_bps: 0,
+            hook_tokens: vec![],
         }
     );
 }
@@ -269,13 +339,11 @@ fn test_blacklist() {
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
             admin: Some(admin.to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
             auto: Some(AssetInfo::Token {
                 contract_addr: Addr::unchecked("auto"),
             }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -309,9 +377,8 @@ fn test_blacklist() {
     // Create request fails
     let request_info = CreateRequestInfo {
         target: "contract001".to_string(),
-        msg: to_binary("").unwrap(),
-        input_asset: None,
         is_recurring: true,
+        ..default_request_info()
     };
     let info = mock_info("addr0000", &[]);
     let err = execute(
@@ -363,14 +430,11 @@ fn test_create_request() {
 
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
             auto: Some(AssetInfo::Token {
                 contract_addr: Addr::unchecked("auto"),
             }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -384,10 +448,9 @@ fn test_create_request() {
         amount: Uint128::from(10u128),
     };
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
         input_asset: Some(input_asset.clone()),
         is_recurring: true,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info,
@@ -411,10 +474,8 @@ fn test_create_request() {
 
     // Insufficient Fee
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
         input_asset: Some(input_asset),
-        is_recurring: false,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info: request_info.clone(),
@@ -496,7 +557,7 @@ fn test_create_request() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 1,
             total_stake_amount: Uint128::zero(),
-            stakes_len: 0
+            num_stakers: 0
         }
     );
     assert_eq!(
@@ -510,9 +571,23 @@ fn test_create_request() {
                 user: "addr0000".to_string(),
                 target: request_info.target,
                 msg: request_info.msg,
-                input_asset: request_info.input_asset,
+                input_assets: request_info.input_asset.into_iter().collect(),
                 is_recurring: request_info.is_recurring,
-                created_at: env.block.time.seconds()
+                created_at: env.block.time.seconds(),
+                catch_failure: false,
+                status: RequestStatus::Pending,
+                valid_from: None,
+                expires_at: None,
+                recurring_schedule: None,
+                last_executed_at: None,
+                executions_done: 0,
+                fee: Some(Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "utest".to_string(),
+                    },
+                    amount: Uint128::from(10000u128),
+                }),
+                pending_owner: None,
             }
         }
     );
@@ -525,10 +600,8 @@ fn test_create_request() {
         amount: Uint128::from(10u128),
     };
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
         input_asset: Some(input_asset.clone()),
-        is_recurring: false,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info,
@@ -558,10 +631,8 @@ fn test_create_request() {
 
     // no input asset, recurring
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
-        input_asset: None,
         is_recurring: true,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info: request_info.clone(),
@@ -585,7 +656,7 @@ fn test_create_request() {
             attr("user", "addr0000".to_string()),
             attr("target", request_info.target.clone()),
             attr("msg", request_info.msg.to_string()),
-            attr("asset", "None"),
+            attr("assets", "[]"),
             attr("is_recurring", "true"),
             attr("created_at", "1000".to_string()),
         ],
@@ -603,7 +674,7 @@ fn test_create_request() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 3,
             total_stake_amount: Uint128::zero(),
-            stakes_len: 0
+            num_stakers: 0
         }
     );
 
@@ -628,9 +699,18 @@ fn test_create_request() {
                 user: "addr0000".to_string(),
                 target: request_info.target,
                 msg: request_info.msg,
-                input_asset: request_info.input_asset,
+                input_assets: request_info.input_asset.into_iter().collect(),
                 is_recurring: request_info.is_recurring,
-                created_at: env.block.time.seconds()
+                created_at: env.block.time.seconds(),
+                catch_failure: false,
+                status: RequestStatus::Pending,
+                valid_from: None,
+                expires_at: None,
+                recurring_schedule: None,
+                last_executed_at: None,
+                executions_done: 0,
+                fee: None,
+                pending_owner: None,
             }
         }
     );
@@ -664,14 +744,11 @@ fn test_cancel_request() {
 
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
             auto: Some(AssetInfo::Token {
                 contract_addr: Addr::unchecked("auto"),
             }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -685,10 +762,8 @@ fn test_cancel_request() {
         amount: Uint128::from(10u128),
     };
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
         input_asset: Some(input_asset0),
-        is_recurring: false,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info,
@@ -717,10 +792,8 @@ fn test_cancel_request() {
         amount: Uint128::from(10u128),
     };
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
         input_asset: Some(input_asset),
-        is_recurring: false,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info
@@ -736,10 +809,8 @@ fn test_cancel_request() {
 
     // Request 2, no input asset, recurring
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
-        input_asset: None,
         is_recurring: true,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info
@@ -784,9 +855,18 @@ fn test_cancel_request() {
                 user: "".to_string(),
                 target: "".to_string(),
                 msg: to_binary("").unwrap(),
-                input_asset: None,
+                input_assets: vec![],
                 is_recurring: false,
-                created_at: 0
+                created_at: 0,
+                catch_failure: false,
+                status: RequestStatus::Pending,
+                valid_from: None,
+                expires_at: None,
+                recurring_schedule: None,
+                last_executed_at: None,
+                executions_done: 0,
+                fee: None,
+                pending_owner: None,
             }
         }
     );
@@ -802,7 +882,7 @@ fn test_cancel_request() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 3,
             total_stake_amount: Uint128::zero(),
-            stakes_len: 0
+            num_stakers: 0
         }
     );
 
@@ -834,14 +914,8 @@ fn test_stake_denom() {
     )]);
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
-            auto: Some(AssetInfo::NativeToken {
-                denom: "uauto".to_string(),
-            }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -899,7 +973,7 @@ fn test_stake_denom() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 0,
             total_stake_amount: Uint128::from(4000u128),
-            stakes_len: 4
+            num_stakers: 1
         }
     );
     assert_eq!(
@@ -927,15 +1001,7 @@ fn test_stake_denom() {
         .unwrap(),
     )
     .unwrap();
-    assert_eq!(
-        stakes.stakes,
-        vec![
-            "addr0000".to_string(),
-            "addr0000".to_string(),
-            "addr0000".to_string(),
-            "addr0000".to_string(),
-        ]
-    );
+    assert_eq!(stakes.stakes, vec!["addr0000".to_string()]);
 
     // error when stake cw20 called
     let info = mock_info("auto", &[]);
@@ -958,14 +1024,11 @@ fn test_stake_cw20() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
             auto: Some(AssetInfo::Token {
                 contract_addr: Addr::unchecked("auto"),
             }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -1031,7 +1094,7 @@ fn test_stake_cw20() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 0,
             total_stake_amount: Uint128::from(2000u128),
-            stakes_len: 2
+            num_stakers: 1
         }
     );
     assert_eq!(
@@ -1059,10 +1122,7 @@ fn test_stake_cw20() {
         .unwrap(),
     )
     .unwrap();
-    assert_eq!(
-        stakes.stakes,
-        vec!["addr0000".to_string(), "addr0000".to_string(),]
-    );
+    assert_eq!(stakes.stakes, vec!["addr0000".to_string()]);
 
     // error when stake denom
     let info = mock_info(
@@ -1102,14 +1162,8 @@ fn test_unstake() {
     )]);
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
-            auto: Some(AssetInfo::NativeToken {
-                denom: "uauto".to_string(),
-            }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -1190,7 +1244,7 @@ fn test_unstake() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 0,
             total_stake_amount: Uint128::from(6000u128),
-            stakes_len: 6
+            num_stakers: 2
         }
     );
     assert_eq!(
@@ -1218,45 +1272,33 @@ fn test_unstake() {
         .unwrap(),
     )
     .unwrap();
-    assert_eq!(
-        stakes.stakes,
-        vec![
-            "addr0".to_string(),
-            "addr0".to_string(),
-            "addr1".to_string(),
-            "addr0".to_string(),
-            "addr1".to_string(),
-            "addr1".to_string(),
-        ]
-    );
+    assert_eq!(stakes.stakes, vec!["addr0".to_string(), "addr1".to_string()]);
 
-    // IdxOutOfBound
+    // InsufficientStake
     let info = mock_info("addr0", &[]);
     let err = execute(
         deps.as_mut(),
         mock_env(),
         info.clone(),
-        ExecuteMsg::Unstake { idxs: vec![7] },
+        ExecuteMsg::Unstake { num_stakes: 4 },
     )
     .err();
-    assert_eq!(err, Some(ContractError::IdxOutOfBound {}));
+    assert_eq!(err, Some(ContractError::InsufficientStake {}));
 
     let err = execute(
         deps.as_mut(),
         mock_env(),
         info.clone(),
-        ExecuteMsg::Unstake { idxs: vec![2] },
+        ExecuteMsg::Unstake { num_stakes: 0 },
     )
     .err();
-    assert_eq!(err, Some(ContractError::IdxNotYou {}));
+    assert_eq!(err, Some(ContractError::InvalidStakeInfo {}));
 
     let res = execute(
         deps.as_mut(),
         mock_env(),
         info,
-        ExecuteMsg::Unstake {
-            idxs: vec![0, 1, 3],
-        },
+        ExecuteMsg::Unstake { num_stakes: 3 },
     )
     .unwrap();
     assert_eq!(
@@ -1279,7 +1321,7 @@ fn test_unstake() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 0,
             total_stake_amount: Uint128::from(3000u128),
-            stakes_len: 3
+            num_stakers: 1
         }
     );
     assert_eq!(
@@ -1307,14 +1349,7 @@ fn test_unstake() {
         .unwrap(),
     )
     .unwrap();
-    assert_eq!(
-        stakes.stakes,
-        vec![
-            "addr1".to_string(),
-            "addr1".to_string(),
-            "addr1".to_string(),
-        ]
-    );
+    assert_eq!(stakes.stakes, vec!["addr1".to_string()]);
 }
 
 #[test]
@@ -1328,16 +1363,7 @@ fn test_update_executor() {
         ],
     )]);
     let msg = InstantiateMsg {
-        config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
-            auto: Some(AssetInfo::NativeToken {
-                denom: "uauto".to_string(),
-            }),
-            stake_amount: Some(Uint128::from(1000u128)),
-            blocks_in_epoch: Some(100),
-        },
+        config: default_config(),
     };
     let info = mock_info("addr0000", &[]);
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1406,14 +1432,8 @@ fn test_recurring_fee() {
     )]);
     let msg = InstantiateMsg {
         config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
-            auto: Some(AssetInfo::NativeToken {
-                denom: "uauto".to_string(),
-            }),
-            stake_amount: Some(Uint128::from(1000u128)),
             blocks_in_epoch: Some(1),
+            ..default_config()
         },
     };
     let info = mock_info("addr0000", &[]);
@@ -1471,7 +1491,7 @@ fn test_recurring_fee() {
             total_recurring_fee: Uint128::from(40000u128),
             next_request_id: 0,
             total_stake_amount: Uint128::zero(),
-            stakes_len: 0
+            num_stakers: 0
         }
     );
     assert_eq!(
@@ -1528,7 +1548,7 @@ fn test_recurring_fee() {
             total_recurring_fee: Uint128::from(10000u128),
             next_request_id: 0,
             total_stake_amount: Uint128::zero(),
-            stakes_len: 0
+            num_stakers: 0
         }
     );
     assert_eq!(
@@ -1567,14 +1587,7 @@ fn test_execute_request() {
         ],
     )]);
     let msg = InstantiateMsg {
-        config: CreateOrUpdateConfig {
-            admin: Some("admin".to_string()),
-            fee_amount: Some(Uint128::from(10000u128)),
-            fee_denom: Some("utest".to_string()),
-            auto: Some(AssetInfo::NativeToken { denom: "uauto".to_string() }),
-            stake_amount: Some(Uint128::from(1000u128)),
-            blocks_in_epoch: Some(100),
-        },
+        config: default_config(),
     };
     let info = mock_info("addr0000", &[]);
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -1587,10 +1600,8 @@ fn test_execute_request() {
         amount: Uint128::from(10u128),
     };
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
         input_asset: Some(input_asset0),
-        is_recurring: false,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info,
@@ -1619,10 +1630,8 @@ fn test_execute_request() {
         amount: Uint128::from(10u128),
     };
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
         input_asset: Some(input_asset),
-        is_recurring: false,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info,
@@ -1639,10 +1648,8 @@ fn test_execute_request() {
 
     // Request 2, no input asset, recurring
     let request_info = CreateRequestInfo {
-        target: "contract0000".to_string(),
-        msg: to_binary("").unwrap(),
-        input_asset: None,
         is_recurring: true,
+        ..default_request_info()
     };
     let msg = ExecuteMsg::CreateRequest {
         request_info,
@@ -1678,13 +1685,42 @@ fn test_execute_request() {
     let mut env = mock_env();
     env.block.height = 100001;
     let epoch = env.block.height / 100 * 100;
-    let _res = execute(
+    let res = execute(
         deps.as_mut(),
         env.clone(),
         info,
         ExecuteMsg::ExecuteRequest { id: 1 },
     )
     .unwrap();
+
+    // CW20 input asset is sent to the target with the request msg
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg {
+                id: 1,
+                msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "contract000".to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Send {
+                        contract: "contract0000".to_string(),
+                        amount: Uint128::from(10u128),
+                        msg: to_binary("").unwrap(),
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                gas_limit: None,
+                reply_on: ReplyOn::Success,
+            },
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "executor".to_string(),
+                amount: vec![Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128)
+                }],
+            }))
+        ],
+    );
     assert_eq!(
         from_binary::<EpochInfoResponse>(
             &query(deps.as_ref(), env.clone(), QueryMsg::EpochInfo {}).unwrap()
@@ -1739,24 +1775,15 @@ fn test_execute_request() {
     assert_eq!(
         res.messages,
         vec![
-            SubMsg {
-                id: 0,
-                msg: CosmosMsg::Bank(BankMsg::Send {
-                    to_address: "contract0000".to_string(),
-                    amount: vec![Coin {
-                        denom: "uosmo".to_string(),
-                        amount: Uint128::from(10u128)
-                    }],
-                }),
-                gas_limit: None,
-                reply_on: ReplyOn::Never,
-            },
             SubMsg {
                 id: 1,
                 msg: CosmosMsg::Wasm(WasmMsg::Execute {
                     contract_addr: "contract0000".to_string(),
                     msg: to_binary("").unwrap(),
-                    funds: vec![],
+                    funds: vec![Coin {
+                        denom: "uosmo".to_string(),
+                        amount: Uint128::from(10u128)
+                    }],
                 }),
                 gas_limit: None,
                 reply_on: ReplyOn::Success,
@@ -1781,7 +1808,7 @@ fn test_execute_request() {
             total_recurring_fee: Uint128::zero(),
             next_request_id: 3,
             total_stake_amount: Uint128::from(2000u128),
-            stakes_len: 2
+            num_stakers: 1
         }
     );
 
@@ -1831,6 +1858,30 @@ fn test_execute_request() {
         ExecuteMsg::ExecuteRequest { id: 2 },
     )
     .unwrap();
+
+    // Target is called even without input assets
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg {
+                id: 1,
+                msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "contract0000".to_string(),
+                    msg: to_binary("").unwrap(),
+                    funds: vec![],
+                }),
+                gas_limit: None,
+                reply_on: ReplyOn::Success,
+            },
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "executor".to_string(),
+                amount: vec![Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128)
+                }],
+            }))
+        ],
+    );
     assert_eq!(
         res.attributes,
         vec![
@@ -1852,7 +1903,7 @@ fn test_execute_request() {
             total_recurring_fee: Uint128::from(10000u128),
             next_request_id: 3,
             total_stake_amount: Uint128::from(2000u128),
-            stakes_len: 2
+            num_stakers: 1
         }
     );
     assert_eq!(
@@ -1866,9 +1917,18 @@ fn test_execute_request() {
                 user: "".to_string(),
                 target: "".to_string(),
                 msg: to_binary("").unwrap(),
-                input_asset: None,
+                input_assets: vec![],
                 is_recurring: false,
-                created_at: 0
+                created_at: 0,
+                catch_failure: false,
+                status: RequestStatus::Pending,
+                valid_from: None,
+                expires_at: None,
+                recurring_schedule: None,
+                last_executed_at: None,
+                executions_done: 0,
+                fee: None,
+                pending_owner: None,
             }
         }
     );
@@ -1922,7 +1982,11 @@ fn test_execute_request() {
     .unwrap();
     assert_eq!(
         res.attributes,
-        vec![attr("action", "finalize_execute")]
+        vec![
+            attr("action", "finalize_execute"),
+            attr("id", "2"),
+            attr("success", "true"),
+        ]
     );
     assert_eq!(
         from_binary::<StateResponse>(
@@ -1935,7 +1999,3836 @@ fn test_execute_request() {
             total_recurring_fee: Uint128::from(10000u128),
             next_request_id: 3,
             total_stake_amount: Uint128::from(2000u128),
-            stakes_len: 2
+            num_stakers: 1
+        }
+    );
+}
+
+#[test]
+fn test_execute_request_without_input_asset() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Request 0, no input asset, not recurring
+    let request_info = CreateRequestInfo {
+        msg: to_binary("msg").unwrap(),
+        ..default_request_info()
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    );
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+
+    // Nobody staked, so anyone can execute
+    let info = mock_info("executor", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg {
+                id: 1,
+                msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "contract0000".to_string(),
+                    msg: to_binary("msg").unwrap(),
+                    funds: vec![],
+                }),
+                gas_limit: None,
+                reply_on: ReplyOn::Success,
+            },
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "executor".to_string(),
+                amount: vec![Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128)
+                }],
+            }))
+        ],
+    );
+    assert_eq!(
+        from_binary::<StateResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()
+        )
+        .unwrap(),
+        StateResponse {
+            curr_executing_request_id: 0,
+            total_requests: 0,
+            total_recurring_fee: Uint128::zero(),
+            next_request_id: 1,
+            total_stake_amount: Uint128::zero(),
+            num_stakers: 0
         }
     );
 }
+
+#[test]
+fn test_execute_request_catch_failure() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Request 0, catches failures
+    let input_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uosmo".to_string(),
+        },
+        amount: Uint128::from(10u128),
+    };
+    let request_info = CreateRequestInfo {
+        input_asset: Some(input_asset.clone()),
+        catch_failure: Some(true),
+        ..default_request_info()
+    };
+    let info = mock_info(
+        "addr0000",
+        &[
+            Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            },
+            Coin {
+                denom: "uosmo".to_string(),
+                amount: Uint128::from(10u128),
+            },
+        ],
+    );
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+
+    // Target call replies always
+    let info = mock_info("executor", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Always);
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "executor".to_string(),
+            amount: vec![Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128)
+            }],
+        }))
+    );
+
+    // Target failed
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Err("target error".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "finalize_execute"),
+            attr("id", "0"),
+            attr("success", "false"),
+        ]
+    );
+
+    // Request is kept as failed
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.status, RequestStatus::Failed);
+    assert_eq!(
+        from_binary::<StateResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()
+        )
+        .unwrap(),
+        StateResponse {
+            curr_executing_request_id: u64::MAX,
+            total_requests: 1,
+            total_recurring_fee: Uint128::zero(),
+            next_request_id: 1,
+            total_stake_amount: Uint128::zero(),
+            num_stakers: 0
+        }
+    );
+    assert_eq!(
+        from_binary::<RequestExecutionsResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RequestExecutions {
+                    id: 0,
+                    start_after: None,
+                    limit: None
+                }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        RequestExecutionsResponse {
+            id: 0,
+            executions: vec![RequestExecution {
+                execution: 1,
+                executor: "executor".to_string(),
+                executed_at: mock_env().block.height,
+                success: false,
+                error: Some("target error".to_string()),
+            }]
+        }
+    );
+
+    // Failed request can't be executed again
+    let info = mock_info("executor", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .err();
+    assert_eq!(err, Some(ContractError::RequestFailed {}));
+
+    // Cancel returns only the input asset, fee was paid to the executor
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CancelRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uosmo".to_string(),
+                amount: Uint128::from(10u128)
+            }],
+        }))]
+    );
+}
+
+#[test]
+fn test_request_expiry() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            expiry_bounty_bps: Some(1000),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(
+        "addr0000",
+        &[
+            Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            },
+            Coin {
+                denom: "uosmo".to_string(),
+                amount: Uint128::from(10u128),
+            },
+        ],
+    );
+    let mut request_info = CreateRequestInfo {
+        input_asset: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uosmo".to_string(),
+            },
+            amount: Uint128::from(10u128),
+        }),
+        valid_from: Some(Scheduled::AtHeight(12_400)),
+        expires_at: Some(Expiration::AtHeight(12_345)),
+        ..default_request_info()
+    };
+
+    // Already expired
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::CreateRequest {
+            request_info: request_info.clone(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidExpiration {}));
+
+    request_info.expires_at = Some(Expiration::AtHeight(12_500));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+
+    // Not valid yet
+    let info = mock_info("executor", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    );
+    assert_eq!(res, Err(ContractError::RequestNotValidYet {}));
+
+    // Expired
+    let mut env = mock_env();
+    env.block.height = 12_500;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    );
+    assert_eq!(res, Err(ContractError::RequestExpired {}));
+
+    // Only owner can cancel before expiry
+    let info = mock_info("addr0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::CancelRequest { id: 0 },
+    );
+    assert_eq!(res, Err(CommonError::Unauthorized {}.into()));
+
+    // Anyone can cancel after expiry and gets the bounty
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::CancelRequest { id: 0 }).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "cancel_request"),
+            attr("id", "0"),
+            attr("bounty", "1000"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uosmo".to_string(),
+                    amount: Uint128::from(10u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(9000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0001".to_string(),
+                amount: vec![Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(1000u128),
+                }],
+            })),
+        ]
+    );
+}
+
+#[test]
+fn test_recurring_schedule() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(30000u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositRecurringFee { recurring_count: 3 },
+    )
+    .unwrap();
+
+    let mut request_info = CreateRequestInfo {
+        recurring_schedule: Some(RecurringSchedule {
+            interval: Duration::Height(10),
+            max_executions: Some(2),
+        }),
+        ..default_request_info()
+    };
+
+    // Only recurring requests can have a schedule
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::CreateRequest {
+            request_info: request_info.clone(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::ScheduleForNonRecurring {}));
+
+    request_info.is_recurring = true;
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+
+    // First execution
+    let info = mock_info("executor", &[]);
+    let mut env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.last_executed_at, Some(Scheduled::AtHeight(12_345)));
+    assert_eq!(request.executions_done, 1);
+
+    // Interval can't switch to seconds after an execution recorded by height
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: UpdateRequestInfo {
+                msg: None,
+                input_assets: None,
+                recurring_schedule: Some(RecurringSchedule {
+                    interval: Duration::Time(10),
+                    max_executions: Some(2),
+                }),
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Common(CommonError::InvalidParam {
+            param_name: "interval".to_string(),
+            invalid_value: "time: 10".to_string(),
+            predicate: "in blocks".to_string(),
+        })
+    );
+
+    // Interval not passed
+    env.block.height = 12_354;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    );
+    assert_eq!(res, Err(ContractError::RequestNotDue {}));
+
+    // Second execution reaches the cap and removes the request
+    env.block.height = 12_355;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    let state = from_binary::<StateResponse>(
+        &query(deps.as_ref(), env, QueryMsg::State {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state.total_requests, 0);
+    assert_eq!(state.total_recurring_fee, Uint128::from(10000u128));
+}
+
+#[test]
+fn test_requests_by_user_and_target() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Request 0 and 2 by addr0000, 1 by addr0001
+    for (user, target) in [
+        ("addr0000", "contract0000"),
+        ("addr0001", "contract0000"),
+        ("addr0000", "contract0001"),
+    ] {
+        let info = mock_info(
+            user,
+            &[Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        );
+        let request_info = CreateRequestInfo {
+            target: target.to_string(),
+            ..default_request_info()
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CreateRequest { request_info },
+        )
+        .unwrap();
+    }
+
+    fn ids(deps: Deps, msg: QueryMsg) -> Vec<u64> {
+        from_binary::<RequestsResponse>(&query(deps, mock_env(), msg).unwrap())
+            .unwrap()
+            .requests
+            .into_iter()
+            .map(|r| r.id)
+            .collect()
+    }
+    assert_eq!(
+        ids(
+            deps.as_ref(),
+            QueryMsg::RequestsByUser {
+                user: "addr0000".to_string(),
+                start_after: None,
+                limit: None,
+            }
+        ),
+        vec![0, 2]
+    );
+    assert_eq!(
+        ids(
+            deps.as_ref(),
+            QueryMsg::RequestsByUser {
+                user: "addr0000".to_string(),
+                start_after: Some(0),
+                limit: None,
+            }
+        ),
+        vec![2]
+    );
+    assert_eq!(
+        ids(
+            deps.as_ref(),
+            QueryMsg::RequestsByTarget {
+                target: "contract0000".to_string(),
+                start_after: None,
+                limit: Some(1),
+            }
+        ),
+        vec![0]
+    );
+    assert_eq!(
+        ids(
+            deps.as_ref(),
+            QueryMsg::RequestsByTarget {
+                target: "contract0000".to_string(),
+                start_after: None,
+                limit: None,
+            }
+        ),
+        vec![0, 1]
+    );
+
+    // Cancelled requests are removed from the indexes
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CancelRequest { id: 0 }).unwrap();
+    assert_eq!(
+        ids(
+            deps.as_ref(),
+            QueryMsg::RequestsByTarget {
+                target: "contract0000".to_string(),
+                start_after: None,
+                limit: None,
+            }
+        ),
+        vec![1]
+    );
+}
+
+#[test]
+fn test_migrate_legacy_storage() {
+    let mut deps = mock_dependencies(&[]);
+    set_contract_version(deps.as_mut().storage, "autonomy-registry-stake", "0.1.0").unwrap();
+
+    // State stored by the previous version, with one address per stake unit
+    deps.as_mut().storage.set(
+        b"state",
+        br#"{"curr_executing_request_id":18446744073709551615,"next_request_id":4,"total_requests":1,"total_recurring_fee":"0","total_staked":"3000","stakes":["addr0","addr1","addr0"],"last_epoch":0,"executor":""}"#,
+    );
+    let stake_balance: Map<&Addr, Uint128> = Map::new("stake_balance");
+    stake_balance
+        .save(deps.as_mut().storage, &Addr::unchecked("addr0"), &Uint128::from(2000u128))
+        .unwrap();
+    stake_balance
+        .save(deps.as_mut().storage, &Addr::unchecked("addr1"), &Uint128::from(1000u128))
+        .unwrap();
+
+    // Request stored by the previous version, with a single input asset and without indexes
+    let legacy_requests: Map<u64, Request> = Map::new("requests");
+    deps.as_mut().storage.set(
+        &legacy_requests.key(3),
+        format!(
+            r#"{{"user":"addr0000","target":"contract0000","msg":"IiI=","input_asset":{{"info":{{"native_token":{{"denom":"uosmo"}}}},"amount":"10"}},"is_recurring":false,"created_at":{},"catch_failure":false,"status":"pending"}}"#,
+            mock_env().block.time.seconds()
+        )
+        .as_bytes(),
+    );
+    let request = Request {
+        user: "addr0000".to_string(),
+        target: "contract0000".to_string(),
+        msg: to_binary("").unwrap(),
+        input_assets: vec![Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uosmo".to_string(),
+            },
+            amount: Uint128::from(10u128),
+        }],
+        is_recurring: false,
+        created_at: mock_env().block.time.seconds(),
+        catch_failure: false,
+        status: RequestStatus::Pending,
+        valid_from: None,
+        expires_at: None,
+        recurring_schedule: None,
+        last_executed_at: None,
+        executions_done: 0,
+        fee: None,
+        pending_owner: None,
+    };
+
+    // Config stored by the previous version
+    deps.as_mut().storage.set(
+        b"config",
+        br#"{"fee_amount":"10000","fee_denom":"utest","auto":{"native_token":{"denom":"uauto"}},"stake_amount":"1000","blocks_in_epoch":100}"#,
+    );
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    // Escrowed fee is backfilled by the config
+    let request = Request {
+        fee: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "utest".to_string(),
+            },
+            amount: Uint128::from(10000u128),
+        }),
+        pending_owner: None,
+        ..request
+    };
+
+    assert_eq!(
+        from_binary::<RequestsResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RequestsByUser {
+                    user: "addr0000".to_string(),
+                    start_after: None,
+                    limit: None,
+                }
+            )
+            .unwrap()
+        )
+        .unwrap(),
+        RequestsResponse {
+            requests: vec![RequestInfoResponse { id: 3, request }]
+        }
+    );
+
+    // Stakes vector is converted to one slot per staker
+    let state = from_binary::<StateResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state.num_stakers, 2);
+    assert_eq!(state.total_stake_amount, Uint128::from(3000u128));
+    assert_eq!(
+        from_binary::<StakesResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Stakes { start: 0, limit: 10 },
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .stakes,
+        vec!["addr0".to_string(), "addr1".to_string()]
+    );
+}
+
+#[test]
+fn test_execute_requests() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Request 1 calls a blacklisted target, request 2 catches failures
+    for (target, catch_failure) in [
+        ("contract0000", None),
+        ("contract0001", None),
+        ("contract0000", Some(true)),
+    ] {
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        );
+        let request_info = CreateRequestInfo {
+            target: target.to_string(),
+            catch_failure,
+            ..default_request_info()
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CreateRequest { request_info },
+        )
+        .unwrap();
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::AddToBlacklist {
+            addrs: vec!["contract0001".to_string()],
+        },
+    )
+    .unwrap();
+
+    // Fails on the blacklisted target unless skipped
+    let info = mock_info("executor", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ExecuteRequests {
+            ids: vec![1, 0],
+            skip_failed: false,
+        },
+    )
+    .err();
+    assert_eq!(err, Some(ContractError::TargetBlacklisted {}));
+
+    // Ids can't be repeated
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::ExecuteRequests {
+            ids: vec![0, 2, 0],
+            skip_failed: true,
+        },
+    )
+    .err();
+    assert_eq!(err, Some(ContractError::DuplicateRequestIds {}));
+
+    // Blacklisted request 1 and missing request 5 are skipped
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::ExecuteRequests {
+            ids: vec![0, 1, 5, 2],
+            skip_failed: true,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_requests"),
+            attr("executor", "executor"),
+            attr("executed", "0,2"),
+            attr("skipped", "1,5"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                WasmMsg::Execute {
+                    contract_addr: "contract0000".to_string(),
+                    msg: to_binary("").unwrap(),
+                    funds: vec![],
+                },
+                1
+            ),
+            SubMsg::reply_always(
+                WasmMsg::Execute {
+                    contract_addr: "contract0000".to_string(),
+                    msg: to_binary("").unwrap(),
+                    funds: vec![],
+                },
+                1
+            ),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "executor".to_string(),
+                amount: vec![Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(20000u128),
+                }],
+            })),
+        ]
+    );
+
+    // Replies are processed in order of the execution
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "finalize_execute"),
+            attr("id", "0"),
+            attr("success", "true"),
+        ]
+    );
+    let state = from_binary::<StateResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state.curr_executing_request_id, 2);
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Err("target error".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "finalize_execute"),
+            attr("id", "2"),
+            attr("success", "false"),
+        ]
+    );
+    let state = from_binary::<StateResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(state.curr_executing_request_id, u64::MAX);
+    // Skipped request 1 and failed request 2 remain
+    assert_eq!(state.total_requests, 2);
+}
+
+#[test]
+fn test_request_executions_same_block() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let request_info = CreateRequestInfo {
+        is_recurring: true,
+        catch_failure: Some(true),
+        ..default_request_info()
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[Coin::new(20000u128, "utest")]),
+        ExecuteMsg::DepositRecurringFee { recurring_count: 2 },
+    )
+    .unwrap();
+
+    // Recurring request is executed twice in the same block
+    for result in [
+        SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+        SubMsgResult::Err("target error".to_string()),
+    ] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("executor", &[]),
+            ExecuteMsg::ExecuteRequest { id: 0 },
+        )
+        .unwrap();
+        reply(deps.as_mut(), mock_env(), Reply { id: 1, result }).unwrap();
+    }
+
+    // Both executions are kept
+    let query_executions = |deps: Deps, start_after| {
+        from_binary::<RequestExecutionsResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::RequestExecutions {
+                    id: 0,
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .executions
+    };
+    let first = RequestExecution {
+        execution: 1,
+        executor: "executor".to_string(),
+        executed_at: mock_env().block.height,
+        success: true,
+        error: None,
+    };
+    let second = RequestExecution {
+        execution: 2,
+        executor: "executor".to_string(),
+        executed_at: mock_env().block.height,
+        success: false,
+        error: Some("target error".to_string()),
+    };
+    assert_eq!(
+        query_executions(deps.as_ref(), None),
+        vec![first, second.clone()]
+    );
+    assert_eq!(query_executions(deps.as_ref(), Some(1)), vec![second]);
+}
+
+#[test]
+fn test_execution_grace_blocks() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            staker_grace_blocks: Some(10),
+            open_grace_blocks: Some(50),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Anyone can't execute earlier than stakers
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                open_grace_blocks: Some(5),
+                ..default_config()
+            },
+        },
+    )
+    .err();
+    assert_eq!(
+        err,
+        Some(
+            CommonError::InvalidParam {
+                param_name: "open_grace_blocks".to_string(),
+                invalid_value: "5".to_string(),
+                predicate: ">= 10".to_string(),
+            }
+            .into()
+        )
+    );
+
+    // Grace blocks end within the epoch
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: Some(50),
+                ..default_config()
+            },
+        },
+    )
+    .err();
+    assert_eq!(
+        err,
+        Some(
+            CommonError::InvalidParam {
+                param_name: "open_grace_blocks".to_string(),
+                invalid_value: "50".to_string(),
+                predicate: "< 50".to_string(),
+            }
+            .into()
+        )
+    );
+
+    for staker in ["addr0", "addr1"] {
+        let info = mock_info(
+            staker,
+            &[Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(1000u128),
+            }],
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StakeDenom { num_stakes: 1 },
+        )
+        .unwrap();
+    }
+
+    for _ in 0..2 {
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        );
+        let request_info = default_request_info();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CreateRequest { request_info },
+        )
+        .unwrap();
+    }
+
+    // Epoch starts at 12300
+    let mut env = mock_env();
+    env.block.height = 12_305;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateExecutor {},
+    )
+    .unwrap();
+    let executor = from_binary::<EpochInfoResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::EpochInfo {}).unwrap(),
+    )
+    .unwrap()
+    .executor;
+    let idle_staker = if executor == "addr0" { "addr1" } else { "addr0" };
+
+    // Only the executor within the grace blocks
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(idle_staker, &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .err();
+    assert_eq!(err, Some(ContractError::InvalidExecutor {}));
+
+    // Any staker after the staker grace blocks
+    env.block.height = 12_310;
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0002", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .err();
+    assert_eq!(err, Some(ContractError::InvalidExecutor {}));
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(idle_staker, &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+
+    // Anyone after the open grace blocks
+    env.block.height = 12_350;
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0002", &[]),
+        ExecuteMsg::ExecuteRequest { id: 1 },
+    )
+    .unwrap();
+
+    // Grace blocks not given are unset on clear
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                staker_grace_blocks: Some(20),
+                clear_grace_blocks: Some(true),
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+    let config = from_binary::<Config>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.staker_grace_blocks, Some(20));
+    assert_eq!(config.open_grace_blocks, None);
+}
+
+#[test]
+fn test_stake_weighted_executor() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            blocks_in_epoch: Some(1),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (staker, num_stakes) in [("addr0", 3u64), ("addr1", 1), ("addr2", 1)] {
+        let info = mock_info(
+            staker,
+            &[Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(1000u128 * num_stakes as u128),
+            }],
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StakeDenom { num_stakes },
+        )
+        .unwrap();
+    }
+
+    // `addr2` leaves, `addr1` stays as the only other staker
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr2", &[]),
+        ExecuteMsg::Unstake { num_stakes: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<StakesResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Stakes { start: 0, limit: 10 },
+            )
+            .unwrap()
+        )
+        .unwrap()
+        .stakes,
+        vec!["addr0".to_string(), "addr1".to_string()]
+    );
+
+    // Executors are picked proportionally to the stake balances
+    let mut picks = (0, 0);
+    let mut env = mock_env();
+    for height in 20_000..20_400 {
+        env.block.height = height;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr0000", &[]),
+            ExecuteMsg::UpdateExecutor {},
+        )
+        .unwrap();
+        let executor = from_binary::<EpochInfoResponse>(
+            &query(deps.as_ref(), env.clone(), QueryMsg::EpochInfo {}).unwrap(),
+        )
+        .unwrap()
+        .executor;
+        match executor.as_str() {
+            "addr0" => picks.0 += 1,
+            "addr1" => picks.1 += 1,
+            _ => panic!("unexpected executor {}", executor),
+        }
+    }
+    assert!(picks.0 > picks.1 * 2);
+    assert!(picks.1 > 0);
+}
+
+#[test]
+fn test_randomness_source() {
+    // Registry with 4 stakers, where `addr0000` creates `num_requests` requests
+    fn setup(
+        randomness_source: RandomnessSource,
+        num_requests: u64,
+    ) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InstantiateMsg {
+            config: CreateOrUpdateConfig {
+                blocks_in_epoch: Some(1),
+                randomness_source: Some(randomness_source),
+                ..default_config()
+            },
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+        for staker in ["addr0", "addr1", "addr2", "addr3"] {
+            let info = mock_info(
+                staker,
+                &[Coin {
+                    denom: "uauto".to_string(),
+                    amount: Uint128::from(1000u128),
+                }],
+            );
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::StakeDenom { num_stakes: 1 },
+            )
+            .unwrap();
+        }
+
+        for _ in 0..num_requests {
+            let info = mock_info(
+                "addr0000",
+                &[Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128),
+                }],
+            );
+            let request_info = default_request_info();
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info,
+                ExecuteMsg::CreateRequest { request_info },
+            )
+            .unwrap();
+        }
+        deps
+    }
+
+    // Executors of the epochs from the height 20000
+    fn executors(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) -> Vec<String> {
+        let mut env = mock_env();
+        (20_000..20_020)
+            .map(|height| {
+                env.block.height = height;
+                let res = execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    mock_info("addr0000", &[]),
+                    ExecuteMsg::UpdateExecutor {},
+                )
+                .unwrap();
+                res.attributes[2].value.clone()
+            })
+            .collect()
+    }
+
+    // Executors only depend on the height
+    let mut deps_a = setup(RandomnessSource::BlockHeight, 0);
+    let mut deps_b = setup(RandomnessSource::BlockHeight, 1);
+    assert_eq!(executors(&mut deps_a), executors(&mut deps_b));
+
+    // Executors depend on the history of the registry as well
+    let mut deps_a = setup(RandomnessSource::HashChain, 0);
+    let mut deps_b = setup(RandomnessSource::HashChain, 1);
+    assert_ne!(executors(&mut deps_a), executors(&mut deps_b));
+
+    // and differ from the ones predicted by the height
+    let mut deps_c = setup(RandomnessSource::BlockHeight, 0);
+    assert_ne!(executors(&mut deps_a), executors(&mut deps_c));
+}
+
+#[test]
+fn test_unbonding() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            blocks_in_epoch: Some(1),
+            unbonding_period: Some(Duration::Height(100)),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for (staker, num_stakes) in [("addr0", 3u64), ("addr1", 1)] {
+        let info = mock_info(
+            staker,
+            &[Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(1000u128 * num_stakes as u128),
+            }],
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StakeDenom { num_stakes },
+        )
+        .unwrap();
+    }
+
+    // Unstaked AUTO is queued instead of being returned
+    let mut env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::Unstake { num_stakes: 2 },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+    env.block.height += 50;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::Unstake { num_stakes: 1 },
+    )
+    .unwrap();
+    let unbonding = from_binary::<UnbondingResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Unbonding {
+                user: "addr0".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        unbonding.entries,
+        vec![
+            UnbondingEntry {
+                amount: Uint128::from(2000u128),
+                release_at: Expiration::AtHeight(mock_env().block.height + 100),
+            },
+            UnbondingEntry {
+                amount: Uint128::from(1000u128),
+                release_at: Expiration::AtHeight(mock_env().block.height + 150),
+            },
+        ]
+    );
+
+    // Unbonding stakes are not eligible for executor selection
+    for height in 20_000..20_020 {
+        env.block.height = height;
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr0000", &[]),
+            ExecuteMsg::UpdateExecutor {},
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2].value, "addr1");
+    }
+
+    // Nothing to withdraw before the unbonding period
+    let mut env = mock_env();
+    env.block.height += 99;
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::WithdrawUnbonded {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoUnbondedStake {});
+
+    // Only the matured entries are withdrawn
+    env.block.height += 1;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::WithdrawUnbonded {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0".to_string(),
+            amount: vec![Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(2000u128),
+            }],
+        })]
+    );
+    let unbonding = from_binary::<UnbondingResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Unbonding {
+                user: "addr0".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(unbonding.entries.len(), 1);
+
+    env.block.height += 50;
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0", &[]),
+        ExecuteMsg::WithdrawUnbonded {},
+    )
+    .unwrap();
+    let unbonding = from_binary::<UnbondingResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Unbonding {
+                user: "addr0".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(unbonding.entries, vec![]);
+
+    // Unstaking returns AUTO instantly once the unbonding period is cleared
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                clear_unbonding_period: Some(true),
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr1", &[]),
+        ExecuteMsg::Unstake { num_stakes: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr1".to_string(),
+            amount: vec![Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(1000u128),
+            }],
+        })]
+    );
+}
+
+#[test]
+fn test_slash_missed_epoch() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            slash_bps: Some(1000),
+            slash_bounty_bps: Some(1000),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(
+        "addr0",
+        &[Coin {
+            denom: "uauto".to_string(),
+            amount: Uint128::from(3000u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::StakeDenom { num_stakes: 3 },
+    )
+    .unwrap();
+
+    // Nothing was pending during the epoch
+    let mut env = mock_env();
+    env.block.height = 12_400;
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 12_300 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EpochNotMissed {});
+
+    for _ in 0..2 {
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        );
+        let request_info = default_request_info();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            ExecuteMsg::CreateRequest { request_info },
+        )
+        .unwrap();
+    }
+
+    // Requests created in former epochs are pending once reported
+    env.block.height = 20_000;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportPendingRequest { id: 0 },
+    )
+    .unwrap();
+
+    // Epoch is not over yet
+    env.block.height = 20_050;
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_000 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EpochNotMissed {});
+
+    // Only the bounty of the reporter is slashed without treasury
+    env.block.height = 20_100;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_000 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "reporter".to_string(),
+            amount: vec![Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(30u128),
+            }],
+        })]
+    );
+    let stake_amount = from_binary::<StakeAmountResponse>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::StakeAmount {
+                user: "addr0".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(stake_amount.amount, Uint128::from(2970u128));
+
+    // Epoch is slashed only once
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_000 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EpochNotMissed {});
+
+    // Executor who executed during the epoch is not slashed
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    env.block.height = 20_200;
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_100 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EpochNotMissed {});
+
+    // Requests pending for less than the notice blocks before the epoch end don't slash
+    env.block.height = 20_260;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportPendingRequest { id: 1 },
+    )
+    .unwrap();
+    env.block.height = 20_300;
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_200 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EpochNotMissed {});
+
+    // Treasury receives slashed AUTO except the bounty if set
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                treasury: Some("treasury".to_string()),
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportPendingRequest { id: 1 },
+    )
+    .unwrap();
+    env.block.height = 20_400;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_300 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "reporter".to_string(),
+                amount: vec![Coin {
+                    denom: "uauto".to_string(),
+                    amount: Uint128::from(29u128),
+                }],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: vec![Coin {
+                    denom: "uauto".to_string(),
+                    amount: Uint128::from(268u128),
+                }],
+            }),
+        ]
+    );
+
+    // Only whole stakes covered by the slashed balance can be unstaked
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::Unstake { num_stakes: 3 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientStake {});
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::Unstake { num_stakes: 2 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0".to_string(),
+            amount: vec![Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(2000u128),
+            }],
+        })]
+    );
+    let stake_amount = from_binary::<StakeAmountResponse>(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::StakeAmount {
+                user: "addr0".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(stake_amount.amount, Uint128::from(673u128));
+}
+
+#[test]
+fn test_pending_requests() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            slash_bps: Some(1000),
+            slash_bounty_bps: Some(10000),
+            unbonding_period: Some(Duration::Height(1000)),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(
+        "addr0",
+        &[Coin {
+            denom: "uauto".to_string(),
+            amount: Uint128::from(3000u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::StakeDenom { num_stakes: 3 },
+    )
+    .unwrap();
+
+    let request_info = |target: &str, is_recurring: bool, valid_from: Option<Scheduled>| {
+        CreateRequestInfo {
+            target: target.to_string(),
+            is_recurring,
+            valid_from,
+            ..default_request_info()
+        }
+    };
+    let fee = vec![Coin {
+        denom: "utest".to_string(),
+        amount: Uint128::from(10000u128),
+    }];
+
+    // Target of the request is blacklisted later
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &fee),
+        ExecuteMsg::CreateRequest {
+            request_info: request_info("contract0001", false, None),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::AddToBlacklist {
+            addrs: vec!["contract0001".to_string()],
+        },
+    )
+    .unwrap();
+
+    // Requests which can't be executed are not pending
+    let mut env = mock_env();
+    env.block.height = 20_000;
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateExecutor {},
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &fee),
+        ExecuteMsg::CreateRequest {
+            request_info: request_info(
+                "contract0000",
+                false,
+                Some(Scheduled::AtHeight(30_000)),
+            ),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreateRequest {
+            request_info: request_info("contract0000", true, None),
+        },
+    )
+    .unwrap();
+
+    for (id, expected) in [
+        (0, ContractError::TargetBlacklisted {}),
+        (1, ContractError::RequestNotValidYet {}),
+        (2, ContractError::InsufficientRecurringFee {}),
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("reporter", &[]),
+            ExecuteMsg::ReportPendingRequest { id },
+        )
+        .unwrap_err();
+        assert_eq!(err, expected);
+    }
+
+    env.block.height = 20_100;
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_000 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EpochNotMissed {});
+
+    // Recurring request is pending once its pool covers the fee
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &fee),
+        ExecuteMsg::DepositRecurringFee { recurring_count: 1 },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportPendingRequest { id: 2 },
+    )
+    .unwrap();
+
+    // Unstaking doesn't escape the slash
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::Unstake { num_stakes: 3 },
+    )
+    .unwrap();
+    env.block.height = 20_200;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("reporter", &[]),
+        ExecuteMsg::ReportMissedEpoch { epoch: 20_100 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "reporter".to_string(),
+            amount: vec![Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(300u128),
+            }],
+        })]
+    );
+    let unbonding = from_binary::<UnbondingResponse>(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::Unbonding {
+                user: "addr0".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        unbonding.entries,
+        vec![UnbondingEntry {
+            amount: Uint128::from(2700u128),
+            release_at: Expiration::AtHeight(21_100),
+        }]
+    );
+}
+
+#[test]
+fn test_staker_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            open_grace_blocks: Some(0),
+            staker_fee_bps: Some(2000),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    fn stake(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>, staker: &str, num_stakes: u64) {
+        let info = mock_info(
+            staker,
+            &[Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(1000u128 * num_stakes as u128),
+            }],
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StakeDenom { num_stakes },
+        )
+        .unwrap();
+    }
+
+    fn pending_rewards(deps: Deps, user: &str) -> Uint128 {
+        from_binary::<PendingRewardsResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::PendingRewards {
+                    user: user.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .rewards
+        .into_iter()
+        .find(|coin| coin.denom == "utest")
+        .map(|coin| coin.amount)
+        .unwrap_or_default()
+    }
+
+    stake(&mut deps, "addr0", 3);
+    stake(&mut deps, "addr1", 1);
+
+    // Stakers share the fee of a request
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    );
+    let request_info = default_request_info();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(BankMsg::Send {
+            to_address: "executor0".to_string(),
+            amount: vec![Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(8000u128),
+            }],
+        })
+    );
+    assert_eq!(pending_rewards(deps.as_ref(), "addr0"), Uint128::from(1500u128));
+    assert_eq!(pending_rewards(deps.as_ref(), "addr1"), Uint128::from(500u128));
+
+    // Rewards are settled before the stake balance changes
+    stake(&mut deps, "addr1", 1);
+    assert_eq!(pending_rewards(deps.as_ref(), "addr1"), Uint128::from(500u128));
+
+    // Stakers share the recurring fee as well
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositRecurringFee { recurring_count: 1 },
+    )
+    .unwrap();
+    let request_info = CreateRequestInfo {
+        is_recurring: true,
+        ..default_request_info()
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 1 },
+    )
+    .unwrap();
+    assert_eq!(pending_rewards(deps.as_ref(), "addr0"), Uint128::from(2700u128));
+    assert_eq!(pending_rewards(deps.as_ref(), "addr1"), Uint128::from(1300u128));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::ClaimRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0".to_string(),
+            amount: vec![Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(2700u128),
+            }],
+        })]
+    );
+    assert_eq!(pending_rewards(deps.as_ref(), "addr0"), Uint128::zero());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::ClaimRewards {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoRewards {});
+}
+
+#[test]
+fn test_staker_rewards_denoms() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            open_grace_blocks: Some(0),
+            staker_fee_bps: Some(2000),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for staker in ["addr0", "addr1", "addr2"] {
+        let info = mock_info(
+            staker,
+            &[Coin {
+                denom: "uauto".to_string(),
+                amount: Uint128::from(1000u128),
+            }],
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::StakeDenom { num_stakes: 1 },
+        )
+        .unwrap();
+    }
+
+    let create_request = |deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+                          fee: Coin| {
+        let request_info = default_request_info();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0000", &[fee]),
+            ExecuteMsg::CreateRequest { request_info },
+        )
+        .unwrap();
+    };
+    create_request(&mut deps, Coin::new(10000, "utest"));
+    create_request(&mut deps, Coin::new(10000, "utest"));
+
+    // Rounding remainder of the reward per stake is carried to the next execution
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    let index = REWARD_INDEXES.load(&deps.storage, "utest").unwrap();
+    assert_eq!(index.remainder, Uint128::from(1u128));
+
+    // Fee denom changes, the escrowed fee is shared in its own denom
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: Some(Uint128::from(30000u128)),
+                fee_denom: Some("uatom".to_string()),
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 1 },
+    )
+    .unwrap();
+    let index = REWARD_INDEXES.load(&deps.storage, "utest").unwrap();
+    assert_eq!(index.remainder, Uint128::zero());
+    assert_eq!(
+        index.reward_per_stake * Uint128::from(3000u128),
+        Uint128::from(4000u128)
+    );
+
+    create_request(&mut deps, Coin::new(30000, "uatom"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 2 },
+    )
+    .unwrap();
+
+    let pending_rewards = from_binary::<PendingRewardsResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PendingRewards {
+                user: "addr0".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        pending_rewards.rewards,
+        vec![Coin::new(2000, "uatom"), Coin::new(1333, "utest")]
+    );
+
+    // Rewards of every denom are claimed at once
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0", &[]),
+        ExecuteMsg::ClaimRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0".to_string(),
+            amount: vec![Coin::new(2000, "uatom"), Coin::new(1333, "utest")],
+        })]
+    );
+}
+
+#[test]
+fn test_treasury_fee() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            staker_fee_bps: Some(2000),
+            protocol_fee_bps: Some(1000),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Fee shares can't exceed the fee
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                protocol_fee_bps: Some(9000),
+                ..default_config()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        CommonError::InvalidParam {
+            param_name: "protocol_fee_bps".to_string(),
+            invalid_value: "9000".to_string(),
+            predicate: "<= 8000".to_string(),
+        }
+        .into()
+    );
+
+    // Protocol fee is accrued on execution
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    );
+    let request_info = default_request_info();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(BankMsg::Send {
+            to_address: "executor0".to_string(),
+            amount: vec![Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(9000u128),
+            }],
+        })
+    );
+    let treasury = from_binary::<TreasuryResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Treasury {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        treasury,
+        TreasuryResponse {
+            treasury: None,
+            balance: vec![Coin::new(1000, "utest")],
+            withdrawn: vec![],
+        }
+    );
+
+    // Only admin can withdraw to the treasury
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::WithdrawTreasury {},
+    )
+    .err();
+    assert_eq!(err, Some(AdminError::NotAdmin {}.into()));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::WithdrawTreasury {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TreasuryNotSet {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                treasury: Some("treasury".to_string()),
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+
+    // Protocol fee is accrued in the denom of the fee
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: Some(Uint128::from(20000u128)),
+                fee_denom: Some("uatom".to_string()),
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                treasury: Some("treasury".to_string()),
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[Coin::new(20000, "uatom")]),
+        ExecuteMsg::CreateRequest {
+            request_info: default_request_info(),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 1 },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::WithdrawTreasury {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "treasury".to_string(),
+            amount: vec![Coin::new(2000, "uatom"), Coin::new(1000, "utest")],
+        })]
+    );
+    let treasury = from_binary::<TreasuryResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Treasury {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        treasury,
+        TreasuryResponse {
+            treasury: Some("treasury".to_string()),
+            balance: vec![],
+            withdrawn: vec![Coin::new(2000, "uatom"), Coin::new(1000, "utest")],
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::WithdrawTreasury {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoProtocolFee {});
+
+    // Treasury can be cleared
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: None,
+                fee_denom: None,
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                clear_treasury: Some(true),
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+    let treasury = from_binary::<TreasuryResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Treasury {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(treasury.treasury, None);
+}
+
+#[test]
+fn test_request_fee_snapshot() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            protocol_fee_bps: Some(1000),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for _ in 0..2 {
+        let info = mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        );
+        let request_info = default_request_info();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::CreateRequest { request_info },
+        )
+        .unwrap();
+    }
+
+    // Fee changes after the creation
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateConfig {
+            config: CreateOrUpdateConfig {
+                admin: None,
+                fee_amount: Some(Uint128::from(20000u128)),
+                fee_denom: Some("uatom".to_string()),
+                auto: None,
+                stake_amount: None,
+                blocks_in_epoch: None,
+                ..default_config()
+            },
+        },
+    )
+    .unwrap();
+
+    // Escrowed fee is refunded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CancelRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        })]
+    );
+
+    // Escrowed fee is shared in its own denom
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(BankMsg::Send {
+            to_address: "executor0".to_string(),
+            amount: vec![Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(9000u128),
+            }],
+        })
+    );
+    let treasury = from_binary::<TreasuryResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Treasury {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(treasury.balance, vec![Coin::new(1000, "utest")]);
+}
+
+#[test]
+fn test_create_request_refund() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let request_info = CreateRequestInfo {
+        input_asset: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uosmo".to_string(),
+            },
+            amount: Uint128::from(10u128),
+        }),
+        ..default_request_info()
+    };
+
+    // Over-funded call with unrelated denoms
+    let info = mock_info(
+        "addr0000",
+        &[
+            Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(15000u128),
+            },
+            Coin {
+                denom: "uosmo".to_string(),
+                amount: Uint128::from(30u128),
+            },
+            Coin {
+                denom: "ujuno".to_string(),
+                amount: Uint128::from(5u128),
+            },
+        ],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest {
+            request_info: request_info.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![
+                Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(5000u128),
+                },
+                Coin {
+                    denom: "uosmo".to_string(),
+                    amount: Uint128::from(20u128),
+                },
+                Coin {
+                    denom: "ujuno".to_string(),
+                    amount: Uint128::from(5u128),
+                },
+            ],
+        })]
+    );
+
+    // Exactly funded call
+    let info = mock_info(
+        "addr0000",
+        &[
+            Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            },
+            Coin {
+                denom: "uosmo".to_string(),
+                amount: Uint128::from(10u128),
+            },
+        ],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    // Recurring requests don't take funds
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    );
+    let request_info = CreateRequestInfo {
+        is_recurring: true,
+        ..default_request_info()
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "utest".to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        })]
+    );
+}
+
+#[test]
+fn test_create_request_cw20_hook() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: CreateOrUpdateConfig {
+            hook_tokens: Some(vec!["token0000".to_string(), "token0001".to_string()]),
+            ..default_config()
+        },
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let input_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("token0000"),
+        },
+        amount: Uint128::from(50u128),
+    };
+    let request_info = CreateRequestInfo {
+        input_asset: Some(input_asset.clone()),
+        ..default_request_info()
+    };
+    let hook_msg = to_binary(&Cw20HookMsg::CreateRequest {
+        request_info: request_info.clone(),
+    })
+    .unwrap();
+
+    // Fee should be pre-funded
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("token0000", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(50u128),
+            msg: hook_msg.clone(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InsufficientRecurringFee {});
+
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositRecurringFee { recurring_count: 1 },
+    )
+    .unwrap();
+
+    // Unknown token can't charge the fee from the balance of the sender it reports
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fake0000", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(50u128),
+            msg: to_binary(&Cw20HookMsg::CreateRequest {
+                request_info: CreateRequestInfo {
+                    input_asset: Some(Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: Addr::unchecked("fake0000"),
+                        },
+                        amount: Uint128::from(50u128),
+                    }),
+                    ..request_info.clone()
+                },
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+
+    // Received token should match the input asset
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("token0001", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(50u128),
+            msg: hook_msg.clone(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidInputAssets {});
+
+    // Token is escrowed without allowance
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("token0000", &[]),
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(50u128),
+            msg: hook_msg,
+        }),
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.user, "addr0000".to_string());
+    assert_eq!(request.input_assets, vec![input_asset]);
+    assert_eq!(
+        request.fee,
+        Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "utest".to_string(),
+            },
+            amount: Uint128::from(10000u128),
+        })
+    );
+    let recurring_fee = from_binary::<RecurringFeeAmountResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RecurringFees {
+                user: "addr0000".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(recurring_fee.amount, Uint128::zero());
+
+    // Allowance path keeps working
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "utest".to_string(),
+            amount: Uint128::from(10000u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::CreateRequest { request_info },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0000".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+#[test]
+fn test_execute_request_cw20_hook() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Request 0 reverts on failure, request 1 catches failures
+    let input_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("token0000"),
+        },
+        amount: Uint128::from(50u128),
+    };
+    for catch_failure in [None, Some(true)] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0000", &[Coin::new(10000u128, "utest")]),
+            ExecuteMsg::CreateRequest {
+                request_info: CreateRequestInfo {
+                    msg: to_binary("hook").unwrap(),
+                    input_asset: Some(input_asset.clone()),
+                    catch_failure,
+                    ..default_request_info()
+                },
+            },
+        )
+        .unwrap();
+    }
+    let send = |reply_on: ReplyOn| SubMsg {
+        id: 1,
+        msg: CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "contract0000".to_string(),
+                amount: Uint128::from(50u128),
+                msg: to_binary("hook").unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }),
+        gas_limit: None,
+        reply_on,
+    };
+    let current_execution = |deps: Deps| {
+        from_binary::<CurrentExecutionResponse>(
+            &query(deps, mock_env(), QueryMsg::CurrentExecution {}).unwrap(),
+        )
+        .unwrap()
+        .execution
+    };
+
+    // Target receives the request msg in the send hook of the token,
+    // and the failure of the hook reverts the whole execution
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], send(ReplyOn::Success));
+
+    // Target sees the request and the token while handling the hook
+    assert_eq!(
+        current_execution(deps.as_ref()),
+        Some(CurrentExecution {
+            id: 0,
+            user: "addr0000".to_string(),
+            target: "contract0000".to_string(),
+            input_assets: vec![input_asset.clone()],
+        })
+    );
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(current_execution(deps.as_ref()), None);
+
+    // Failed hook of request 1 is caught, and the token stays escrowed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor", &[]),
+        ExecuteMsg::ExecuteRequest { id: 1 },
+    )
+    .unwrap();
+    assert_eq!(res.messages[0], send(ReplyOn::Always));
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Err("hook error".to_string()),
+        },
+    )
+    .unwrap();
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 1 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.status, RequestStatus::Failed);
+    assert_eq!(request.input_assets, vec![input_asset]);
+
+    // Owner takes the token back
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CancelRequest { id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+#[test]
+fn test_multiple_input_assets() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let native_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uosmo".to_string(),
+        },
+        amount: Uint128::from(10u128),
+    };
+    let native_asset1 = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uatom".to_string(),
+        },
+        amount: Uint128::from(5u128),
+    };
+    let token_asset0 = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("token0000"),
+        },
+        amount: Uint128::from(50u128),
+    };
+    let token_asset1 = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("token0001"),
+        },
+        amount: Uint128::from(20u128),
+    };
+    let request_info = CreateRequestInfo {
+        input_asset: Some(native_asset.clone()),
+        input_assets: vec![native_asset1.clone()],
+        ..default_request_info()
+    };
+    let funds = [
+        Coin::new(10000u128, "utest"),
+        Coin::new(10u128, "uosmo"),
+        Coin::new(5u128, "uatom"),
+    ];
+
+    // Assets should be distinct
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds),
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                input_assets: vec![native_asset.clone()],
+                ..request_info.clone()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DuplicateInputAssets {});
+
+    // Assets should have amounts
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds),
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                input_assets: vec![Asset {
+                    amount: Uint128::zero(),
+                    ..native_asset1.clone()
+                }],
+                ..request_info.clone()
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ZeroInputAsset {});
+
+    // Native assets are paid
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds),
+        ExecuteMsg::CreateRequest {
+            request_info: request_info.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(
+        request.input_assets,
+        vec![native_asset.clone(), native_asset1]
+    );
+
+    // Native assets are attached to the call
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: "contract0000".to_string(),
+                msg: to_binary("").unwrap(),
+                funds: vec![Coin::new(10u128, "uosmo"), Coin::new(5u128, "uatom")],
+            },
+            1
+        )
+    );
+
+    // Single CW20 asset is pulled, and refunded on cancel
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds[..1]),
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                input_asset: None,
+                input_assets: vec![token_asset0.clone()],
+                ..request_info.clone()
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0000".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CancelRequest { id: 1 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "token0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128),
+                }],
+            }),
+        ]
+    );
+
+    // CW20 assets can be combined with native assets and other CW20 assets
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds[..2]),
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                input_assets: vec![token_asset0, token_asset1],
+                ..request_info
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+
+    // They are forwarded by the registry calling itself, so transfers revert with the call
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ExecuteRequest { id: 2 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::ForwardInputAssets {}).unwrap(),
+                funds: vec![],
+            },
+            1
+        )
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor0", &[]),
+        ExecuteMsg::ForwardInputAssets {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::ForwardInputAssets {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "token0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "contract0000".to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "token0001".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "contract0000".to_string(),
+                    amount: Uint128::from(20u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "contract0000".to_string(),
+                msg: to_binary("").unwrap(),
+                funds: vec![Coin::new(10u128, "uosmo")],
+            }),
+        ]
+    );
+
+    // Single input asset msg stays compatible
+    let msg: ExecuteMsg = from_binary(&Binary::from(
+        br#"{"create_request":{"request_info":{"target":"contract0000","msg":"IiI=","input_asset":{"info":{"native_token":{"denom":"uosmo"}},"amount":"10"},"is_recurring":false}}}"#
+            .as_slice(),
+    ))
+    .unwrap();
+    assert_eq!(
+        msg,
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                input_asset: Some(native_asset),
+                ..default_request_info()
+            }
+        }
+    );
+}
+
+#[test]
+fn test_update_request() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let native_asset = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uosmo".to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    let token_asset = |token: &str, amount: u128| Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked(token),
+        },
+        amount: Uint128::from(amount),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[
+                Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128),
+                },
+                Coin {
+                    denom: "uosmo".to_string(),
+                    amount: Uint128::from(10u128),
+                },
+            ],
+        ),
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                input_assets: vec![native_asset(10)],
+                ..default_request_info()
+            },
+        },
+    )
+    .unwrap();
+
+    let request_info = UpdateRequestInfo {
+        msg: Some(to_binary("updated").unwrap()),
+        input_assets: Some(vec![native_asset(15)]),
+        recurring_schedule: None,
+    };
+    let osmo = |amount: u128| {
+        vec![Coin {
+            denom: "uosmo".to_string(),
+            amount: Uint128::from(amount),
+        }]
+    };
+
+    // Only the owner can update
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &osmo(5)),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: request_info.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+
+    // Native increase should be paid
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &osmo(4)),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: request_info.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidInputAssets {});
+
+    // Only recurring requests can have a schedule
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: UpdateRequestInfo {
+                msg: None,
+                input_assets: None,
+                recurring_schedule: Some(RecurringSchedule {
+                    interval: Duration::Height(10),
+                    max_executions: Some(2),
+                }),
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ScheduleForNonRecurring {});
+
+    // Only the delta is escrowed, and the overpaid funds are refunded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &osmo(7)),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: request_info.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: osmo(2),
+        })]
+    );
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].ty, "request_updated");
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.msg, to_binary("updated").unwrap());
+    assert_eq!(request.input_assets, vec![native_asset(15)]);
+
+    // Decrease is refunded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: UpdateRequestInfo {
+                msg: None,
+                input_assets: Some(vec![native_asset(5)]),
+                recurring_schedule: None,
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: osmo(10),
+        })]
+    );
+
+    // CW20 asset can be added to native assets, which is pulled
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: UpdateRequestInfo {
+                msg: None,
+                input_assets: Some(vec![native_asset(5), token_asset("token0000", 50)]),
+                recurring_schedule: None,
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "token0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0000".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(50u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // Native assets can be removed, leaving the CW20 asset
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: UpdateRequestInfo {
+                msg: None,
+                input_assets: Some(vec![token_asset("token0000", 50)]),
+                recurring_schedule: None,
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: osmo(5),
+        })]
+    );
+
+    // Blacklisted targets can't be updated
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("admin", &[]),
+        ExecuteMsg::AddToBlacklist {
+            addrs: vec!["contract0000".to_string()],
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: UpdateRequestInfo {
+                msg: None,
+                input_assets: None,
+                recurring_schedule: None,
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TargetBlacklisted {});
+}
+
+#[test]
+fn test_transfer_request() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Both the creator and the new owner deposit recurring fees
+    for user in ["addr0000", "addr0001"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(
+                user,
+                &[Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(20000u128),
+                }],
+            ),
+            ExecuteMsg::DepositRecurringFee { recurring_count: 2 },
+        )
+        .unwrap();
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                is_recurring: true,
+                ..default_request_info()
+            },
+        },
+    )
+    .unwrap();
+
+    // Only the owner can transfer
+    let transfer_msg = ExecuteMsg::TransferRequest {
+        id: 0,
+        new_owner: "addr0001".to_string(),
+        require_accept: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        transfer_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+
+    // Ownership of a recurring request moves only once the new owner accepts
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        transfer_msg.clone(),
+    )
+    .unwrap();
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.user, "addr0000");
+    assert_eq!(request.pending_owner, Some("addr0001".to_string()));
+
+    // Recurring fee isn't charged from the new owner before accepting
+    let recurring_fees = |deps: Deps, user: &str| {
+        from_binary::<RecurringFeeAmountResponse>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::RecurringFees {
+                    user: user.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .amount
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(recurring_fees(deps.as_ref(), "addr0000"), Uint128::from(10000u128));
+    assert_eq!(recurring_fees(deps.as_ref(), "addr0001"), Uint128::from(20000u128));
+
+    // Only the owner can cancel the pending transfer
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::CancelRequestTransfer { id: 0 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CancelRequestTransfer { id: 0 },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::AcceptRequestTransfer { id: 0 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CancelRequestTransfer { id: 0 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoPendingTransfer {});
+
+    // Updating the request cancels the pending transfer
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        transfer_msg.clone(),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::UpdateRequest {
+            id: 0,
+            request_info: UpdateRequestInfo {
+                msg: Some(to_binary("updated").unwrap()),
+                input_assets: None,
+                recurring_schedule: None,
+            },
+        },
+    )
+    .unwrap();
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.pending_owner, None);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        transfer_msg,
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &[]),
+        ExecuteMsg::AcceptRequestTransfer { id: 0 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::AcceptRequestTransfer { id: 0 },
+    )
+    .unwrap();
+    let request = from_binary::<RequestInfoResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id: 0 }).unwrap(),
+    )
+    .unwrap()
+    .request;
+    assert_eq!(request.user, "addr0001");
+    assert_eq!(request.pending_owner, None);
+    let res = from_binary::<RequestsResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RequestsByUser {
+                user: "addr0001".to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.requests.len(), 1);
+
+    // Recurring fee is charged from the new owner once accepted
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(recurring_fees(deps.as_ref(), "addr0000"), Uint128::from(10000u128));
+    assert_eq!(recurring_fees(deps.as_ref(), "addr0001"), Uint128::from(10000u128));
+
+    // The former owner can't cancel anymore
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::CancelRequest { id: 0 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Common(CommonError::Unauthorized {}));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::CancelRequest { id: 0 },
+    )
+    .unwrap();
+
+    // Non recurring request is transferred directly unless acceptance is required
+    for (id, require_accept) in [(1, None), (2, Some(true))] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(
+                "addr0000",
+                &[Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128),
+                }],
+            ),
+            ExecuteMsg::CreateRequest {
+                request_info: default_request_info(),
+            },
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("addr0000", &[]),
+            ExecuteMsg::TransferRequest {
+                id,
+                new_owner: "addr0001".to_string(),
+                require_accept,
+            },
+        )
+        .unwrap();
+    }
+    for (id, user, pending_owner) in [
+        (1, "addr0001", None),
+        (2, "addr0000", Some("addr0001".to_string())),
+    ] {
+        let request = from_binary::<RequestInfoResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::RequestInfo { id }).unwrap(),
+        )
+        .unwrap()
+        .request;
+        assert_eq!(request.user, user);
+        assert_eq!(request.pending_owner, pending_owner);
+    }
+}
+
+#[test]
+fn test_current_execution() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        config: default_config(),
+    };
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let current_execution = |deps: Deps| {
+        from_binary::<CurrentExecutionResponse>(
+            &query(deps, mock_env(), QueryMsg::CurrentExecution {}).unwrap(),
+        )
+        .unwrap()
+        .execution
+    };
+    assert_eq!(current_execution(deps.as_ref()), None);
+
+    let input_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uosmo".to_string(),
+        },
+        amount: Uint128::from(10u128),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            "addr0000",
+            &[
+                Coin {
+                    denom: "utest".to_string(),
+                    amount: Uint128::from(10000u128),
+                },
+                Coin {
+                    denom: "uosmo".to_string(),
+                    amount: Uint128::from(10u128),
+                },
+            ],
+        ),
+        ExecuteMsg::CreateRequest {
+            request_info: CreateRequestInfo {
+                input_asset: Some(input_asset.clone()),
+                ..default_request_info()
+            },
+        },
+    )
+    .unwrap();
+
+    // Target sees the request while it's called
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("executor", &[]),
+        ExecuteMsg::ExecuteRequest { id: 0 },
+    )
+    .unwrap();
+    assert_eq!(
+        current_execution(deps.as_ref()),
+        Some(CurrentExecution {
+            id: 0,
+            user: "addr0000".to_string(),
+            target: "contract0000".to_string(),
+            input_assets: vec![input_asset],
+        })
+    );
+
+    // Idle after the reply
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(current_execution(deps.as_ref()), None);
+}
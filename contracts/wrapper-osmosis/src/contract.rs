@@ -1,23 +1,31 @@
-use std::ops::Sub;
+use std::convert::TryFrom;
+use std::str::FromStr;
 
+use autonomy::error::CommonError;
+use autonomy::registry::assert_registry_execution;
 use cosmwasm_std::{
-    coins, entry_point, to_binary, BankMsg, CosmosMsg, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128, WasmMsg,
+    coins, entry_point, to_binary, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Reply, Response, StdResult, SubMsg, SubMsgResult, Uint128,
 };
 use cw2::{get_contract_version, set_contract_version};
-use osmo_bindings::{OsmosisMsg, OsmosisQuery};
-use osmosis_std::types::osmosis::gamm::v1beta1::{MsgSwapExactAmountIn, SwapAmountInRoute};
+use osmo_bindings::{OsmosisMsg, OsmosisQuerier, OsmosisQuery, SpotPriceResponse};
+use osmosis_std::types::osmosis::gamm::v1beta1::{
+    MsgSwapExactAmountIn, MsgSwapExactAmountInResponse, SwapAmountInRoute,
+};
 use osmosis_std::types::cosmos::base::v1beta1::{Coin as OsmoCoin};
 
 use semver::Version;
 
 use crate::error::WrapperError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
+use crate::msg::{ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::state::{Config, PendingSwap, CONFIG, PENDING_SWAP};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "autonomy-wrapper-osmosis";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Reply id of the swap message
+const SWAP_REPLY_ID: u64 = 1;
 
 /// ## Description
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
@@ -35,10 +43,17 @@ pub fn instantiate(
     deps: DepsMut<OsmosisQuery>,
     _env: Env,
     _info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> StdResult<Response<OsmosisMsg>> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            registry: deps.api.addr_validate(&msg.registry)?,
+        },
+    )?;
+
     Ok(Response::default())
 }
 
@@ -49,9 +64,9 @@ pub fn instantiate(
 ///
 /// * **_env** is an object of type [`Env`].
 ///
-/// * **_msg** is an object of type [`MigrateMsg`].
+/// * **msg** is an object of type [`MigrateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut<OsmosisQuery>, _env: Env, _msg: MigrateMsg) -> Result<Response, WrapperError> {
+pub fn migrate(deps: DepsMut<OsmosisQuery>, _env: Env, msg: MigrateMsg) -> Result<Response, WrapperError> {
     let version: Version = CONTRACT_VERSION.parse()?;
     let storage_version: Version = get_contract_version(deps.storage)?.version.parse()?;
 
@@ -61,6 +76,17 @@ pub fn migrate(deps: DepsMut<OsmosisQuery>, _env: Env, _msg: MigrateMsg) -> Resu
         // If state structure changed in any contract version in the way migration is needed, it
         // should occur here
     }
+
+    if let Some(registry) = msg.registry {
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                registry: deps.api.addr_validate(&registry)?,
+            },
+        )?;
+    } else if CONFIG.may_load(deps.storage)?.is_none() {
+        return Err(WrapperError::RegistryNotSet {});
+    }
     Ok(Response::default())
 }
 
@@ -95,34 +121,74 @@ pub fn execute(
             deps, env, info, user, route, amount_in, denom_in, min_output, max_output, denom_out
         ),
 
-        ExecuteMsg::CheckRange {
+        ExecuteMsg::LimitSwap {
             user,
-            denom,
-            balance_before,
+            route,
+            denom_in,
+            amount_in,
             min_output,
             max_output,
-        } => execute_check_range(
+            denom_out,
+            pool_id,
+            twap_window,
+            target_price,
+        } => execute_limit_swap(
             deps,
             env,
             info,
             user,
-            denom,
-            balance_before,
+            route,
+            amount_in,
+            denom_in,
             min_output,
             max_output,
+            denom_out,
+            pool_id,
+            twap_window,
+            target_price,
+        ),
+
+        ExecuteMsg::StopLoss {
+            user,
+            route,
+            denom_in,
+            amount_in,
+            denom_out,
+            pool_id,
+            twap_window,
+            trigger_price,
+            max_slippage,
+            min_output,
+        } => execute_stop_loss(
+            deps,
+            env,
+            info,
+            user,
+            route,
+            amount_in,
+            denom_in,
+            denom_out,
+            pool_id,
+            twap_window,
+            trigger_price,
+            max_slippage,
+            min_output,
         ),
     }
 }
 
 /// ## Description
 /// Wrap osmosis swap operation between two assets. Returns [`WrapperError`] on failure.
+/// * The input should be attached to the call, so that stray balances can't be swapped.
+/// * The route should end in `denom_out`, which the output is paid in.
+/// * If called by the registry, `user` should own the request being executed.
 ///
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
 ///
 /// * **env** is an object of type [`Env`].
 ///
-/// * **_info** is an object of type [`MessageInfo`].
+/// * **info** is an object of type [`MessageInfo`].
 ///
 /// * **user** is the address that receives the outputs.
 ///
@@ -138,7 +204,7 @@ pub fn execute(
 pub fn execute_swap(
     deps: DepsMut<OsmosisQuery>,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     user: String,
     route: Vec<SwapAmountInRoute>,
     amount_in: Uint128,
@@ -147,46 +213,60 @@ pub fn execute_swap(
     max_output: Uint128,
     denom_out: String,
 ) -> Result<Response<OsmosisMsg>, WrapperError> {
-    let mut msgs: Vec<CosmosMsg<OsmosisMsg>> = vec![];
-    
-    // Prepare swap message
+    let config = CONFIG.load(deps.storage)?;
+    let user_addr = deps.api.addr_validate(&user)?;
+
+    // Registry calls on behalf of the owner of the request being executed
+    if info.sender == config.registry {
+        assert_registry_execution(
+            &deps.querier,
+            &config.registry,
+            &info.sender,
+            &env.contract.address,
+            &user_addr,
+        )?;
+    }
+
+    // Output is paid in the denom the route ends in
+    if route.last().map(|last| &last.token_out_denom) != Some(&denom_out) {
+        return Err(WrapperError::InvalidRoute {});
+    }
+
+    // Verify exactly the input is received, so nothing is left in the wrapper
+    if info.funds != coins(amount_in.u128(), &denom_in) {
+        return Err(WrapperError::InputNotReceived {});
+    }
+
+    // Prepare swap message, output is checked in the reply
     let swap = MsgSwapExactAmountIn {
-        sender:  env.contract.address.to_string(),
+        sender: env.contract.address.to_string(),
         routes: route,
-        token_in: Some(OsmoCoin{
+        token_in: Some(OsmoCoin {
             denom: denom_in,
             amount: amount_in.to_string(),
         }),
         token_out_min_amount: min_output.to_string(),
     };
 
-    msgs.push(swap.into());
-
-    // Read current balance of the output asset
-    let coin_balance = deps
-        .querier
-        .query_balance(env.contract.address.to_string(), denom_out.clone())?;
-
-    // Add msg to check output amount
-    msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: env.contract.address.to_string(),
-        msg: to_binary(&ExecuteMsg::CheckRange {
-            user,
-            denom: denom_out,
-            balance_before: coin_balance.amount,
+    PENDING_SWAP.save(
+        deps.storage,
+        &PendingSwap {
+            user: user_addr,
+            denom_out,
             min_output,
             max_output,
-        })?,
-        funds: vec![],
-    }));
+        },
+    )?;
 
     Ok(Response::new()
         .add_attribute("action", "swap")
-        .add_messages(msgs))
+        .add_submessage(SubMsg::reply_on_success(swap, SWAP_REPLY_ID)))
 }
 
 /// ## Description
-/// Validates swap output result. Returns [`WrapperError`] on failure.
+/// Swap only when the arithmetic TWAP of `denom_in` quoted in `denom_out` over the last
+/// [`twap_window`] seconds reaches [`target_price`]. Returns [`WrapperError`] on failure.
+/// * Fails with [`WrapperError::PriceConditionNotMet`] when the price is not reached yet.
 ///
 /// ## Params
 /// * **deps** is an object of type [`Deps`].
@@ -197,39 +277,270 @@ pub fn execute_swap(
 ///
 /// * **user** is the address that receives the outputs.
 ///
-/// * **denom** of the output coin.
+/// * **route** is route contains several pools connected to output asset.
 ///
-/// * **balance_before** is output token balance before swap.
+/// * **amount** of input asset.
 ///
 /// * **min_output** is minimum output amount.
 ///
 /// * **max_output** is maximum output amount.
-pub fn execute_check_range(
+///
+/// * **pool_id** is the pool to read the TWAP from.
+///
+/// * **twap_window** is the TWAP window up to now, in seconds.
+///
+/// * **target_price** is the minimum TWAP to swap at.
+pub fn execute_limit_swap(
     deps: DepsMut<OsmosisQuery>,
     env: Env,
     info: MessageInfo,
     user: String,
-    denom: String,
-    balance_before: Uint128,
+    route: Vec<SwapAmountInRoute>,
+    amount_in: Uint128,
+    denom_in: String,
     min_output: Uint128,
     max_output: Uint128,
+    denom_out: String,
+    pool_id: u64,
+    twap_window: u64,
+    target_price: Decimal,
+) -> Result<Response<OsmosisMsg>, WrapperError> {
+    let twap = _query_twap(
+        &deps.as_ref(),
+        &env,
+        pool_id,
+        &denom_in,
+        &denom_out,
+        twap_window,
+    )?;
+    if twap < target_price {
+        return Err(WrapperError::PriceConditionNotMet { twap, target_price });
+    }
+
+    execute_swap(
+        deps, env, info, user, route, amount_in, denom_in, min_output, max_output, denom_out,
+    )
+}
+
+/// ## Description
+/// Swap only when the price of `denom_in` quoted in `denom_out` falls to [`trigger_price`].
+/// The price is the arithmetic TWAP over the last [`twap_window`] seconds, or the spot price.
+/// Returns [`WrapperError`] on failure.
+/// * Fails with [`WrapperError::StopNotTriggered`] when the price is above the trigger price.
+/// * The output should be at least [`min_output`] if given, otherwise the input at the trigger price
+///   less [`max_slippage`], so that a price pushed below the trigger doesn't lower the floor.
+/// * The spot price can be moved within the block, so it's only used with [`min_output`].
+///
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **info** is an object of type [`MessageInfo`].
+///
+/// * **user** is the address that receives the outputs.
+///
+/// * **route** is route contains several pools connected to output asset.
+///
+/// * **amount** of input asset.
+///
+/// * **pool_id** is the pool to read the price from.
+///
+/// * **twap_window** is the TWAP window up to now in seconds, spot price is used if not set.
+///
+/// * **trigger_price** is the maximum price to swap at.
+///
+/// * **max_slippage** is the maximum slippage of the output from the trigger price.
+///
+/// * **min_output** is the minimum output amount, required if [`twap_window`] is not set.
+pub fn execute_stop_loss(
+    deps: DepsMut<OsmosisQuery>,
+    env: Env,
+    info: MessageInfo,
+    user: String,
+    route: Vec<SwapAmountInRoute>,
+    amount_in: Uint128,
+    denom_in: String,
+    denom_out: String,
+    pool_id: u64,
+    twap_window: Option<u64>,
+    trigger_price: Decimal,
+    max_slippage: Decimal,
+    min_output: Option<Uint128>,
 ) -> Result<Response<OsmosisMsg>, WrapperError> {
-    // Validate this call
-    if info.sender != env.contract.address {
-        return Err(WrapperError::NotWrapperContract {
-            expected: env.contract.address.into(),
-            actual: info.sender.into(),
+    if max_slippage > Decimal::one() {
+        return Err(CommonError::InvalidParam {
+            param_name: "max_slippage".to_string(),
+            invalid_value: max_slippage.to_string(),
+            predicate: "<= 1".to_string(),
+        }
+        .into());
+    }
+
+    let price = match twap_window {
+        Some(twap_window) => _query_twap(
+            &deps.as_ref(),
+            &env,
+            pool_id,
+            &denom_in,
+            &denom_out,
+            twap_window,
+        )?,
+        None if min_output.is_some() => {
+            _query_spot_price(&deps.as_ref(), pool_id, &denom_in, &denom_out)?
+        }
+        None => {
+            return Err(CommonError::InvalidParam {
+                param_name: "min_output".to_string(),
+                invalid_value: "None".to_string(),
+                predicate: "set if twap_window is not set".to_string(),
+            }
+            .into())
+        }
+    };
+    if price > trigger_price {
+        return Err(WrapperError::StopNotTriggered {
+            price,
+            trigger_price,
         });
     }
 
-    // Query current balance
-    let user_addr = deps.api.addr_validate(&user)?;
-    let cur_balance = deps
+    let min_output =
+        min_output.unwrap_or_else(|| amount_in * (trigger_price * (Decimal::one() - max_slippage)));
+    execute_swap(
+        deps,
+        env,
+        info,
+        user,
+        route,
+        amount_in,
+        denom_in,
+        min_output,
+        Uint128::MAX,
+        denom_out,
+    )
+}
+
+/// ## Description
+/// Returns the spot price of [`denom_in`] quoted in [`denom_out`], without swap fee.
+///
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **pool_id** is the pool to read the price from.
+///
+/// * **denom_in** is the input denom.
+///
+/// * **denom_out** is the output denom.
+fn _query_spot_price(
+    deps: &Deps<OsmosisQuery>,
+    pool_id: u64,
+    denom_in: &str,
+    denom_out: &str,
+) -> StdResult<Decimal> {
+    let res: SpotPriceResponse = deps
         .querier
-        .query_balance(env.contract.address, denom.clone())?;
+        .query(&OsmosisQuery::spot_price(pool_id, denom_in, denom_out).into())?;
+    Ok(res.price)
+}
+
+/// ## Description
+/// Returns the arithmetic TWAP of [`denom_in`] quoted in [`denom_out`] over the last
+/// [`twap_window`] seconds.
+///
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **env** is an object of type [`Env`].
+///
+/// * **pool_id** is the pool to read the TWAP from.
+///
+/// * **denom_in** is the base asset denom.
+///
+/// * **denom_out** is the quote asset denom.
+///
+/// * **twap_window** is the TWAP window up to now, in seconds.
+fn _query_twap(
+    deps: &Deps<OsmosisQuery>,
+    env: &Env,
+    pool_id: u64,
+    denom_in: &str,
+    denom_out: &str,
+    twap_window: u64,
+) -> Result<Decimal, WrapperError> {
+    if twap_window == 0 {
+        return Err(CommonError::InvalidParam {
+            param_name: "twap_window".to_string(),
+            invalid_value: "0".to_string(),
+            predicate: "> 0".to_string(),
+        }
+        .into());
+    }
+
+    // Start time is in milliseconds
+    let start_time = twap_window
+        .checked_mul(1_000_000_000)
+        .and_then(|window| env.block.time.nanos().checked_sub(window))
+        .ok_or_else(|| CommonError::InvalidParam {
+            param_name: "twap_window".to_string(),
+            invalid_value: twap_window.to_string(),
+            predicate: format!("<= {}", env.block.time.seconds()),
+        })?
+        / 1_000_000;
+    let res = OsmosisQuerier::new(&deps.querier).arithmetic_twap_to_now(
+        pool_id,
+        denom_out.to_string(),
+        denom_in.to_string(),
+        start_time as i64,
+    )?;
+    Ok(res.twap)
+}
+
+/// ## Description
+/// The entry point to the contract for processing the reply from the submessage
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`Reply`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(
+    deps: DepsMut<OsmosisQuery>,
+    _env: Env,
+    msg: Reply,
+) -> Result<Response<OsmosisMsg>, WrapperError> {
+    match msg.id {
+        SWAP_REPLY_ID => swap_reply(deps, msg.result),
+        _ => Err(CommonError::Unauthorized {}.into()),
+    }
+}
+
+/// ## Description
+/// Validates swap output result and sends it to the user. Returns [`WrapperError`] on failure.
+///
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`].
+///
+/// * **result** is the swap result, which an object of type [`SubMsgResult`].
+pub fn swap_reply(
+    deps: DepsMut<OsmosisQuery>,
+    result: SubMsgResult,
+) -> Result<Response<OsmosisMsg>, WrapperError> {
+    let PendingSwap {
+        user,
+        denom_out,
+        min_output,
+        max_output,
+    } = PENDING_SWAP.load(deps.storage)?;
+    PENDING_SWAP.remove(deps.storage);
+
+    // Read output amount from the swap response
+    let response = MsgSwapExactAmountInResponse::try_from(result)?;
+    let output = Uint128::from_str(&response.token_out_amount)?;
 
     // Check if the output is in the range
-    let output = cur_balance.amount.sub(balance_before);
     if output.lt(&min_output) || output.gt(&max_output) {
         return Err(WrapperError::InvalidOutput {
             expected_min: min_output,
@@ -240,13 +551,44 @@ pub fn execute_check_range(
 
     // Transfer output asset to the user
     let msgs: Vec<CosmosMsg<OsmosisMsg>> = vec![CosmosMsg::Bank(BankMsg::Send {
-        to_address: user_addr.to_string(),
-        amount: coins(output.u128(), denom),
+        to_address: user.to_string(),
+        amount: coins(output.u128(), denom_out),
     })];
 
     Ok(Response::new()
         .add_messages(msgs)
-        .add_attributes(vec![("action", "execute_check_range")]))
+        .add_attributes(vec![
+            ("action", "finalize_swap"),
+            ("output", &output.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Exposes all the queries available in the contract.
+///
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+///
+/// * **_env** is an object of type [`Env`].
+///
+/// * **msg** is an object of type [`QueryMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<OsmosisQuery>, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+    }
+}
+
+/// ## Description
+/// Returns the wrapper config using [`ConfigResponse`].
+///
+/// ## Params
+/// * **deps** is an object of type [`Deps`].
+pub fn query_config(deps: Deps<OsmosisQuery>) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        registry: config.registry.to_string(),
+    })
 }
 
 #[cfg(test)]
@@ -255,8 +597,11 @@ mod tests {
     use cosmwasm_std::testing::{
         mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
     };
-    use cosmwasm_std::{coins, Coin, SystemResult, SubMsg, attr};
-    use cosmwasm_std::{OwnedDeps, SystemError};
+    use autonomy::error::CommonError;
+    use autonomy::registry::{CurrentExecution, CurrentExecutionResponse};
+    use cosmwasm_std::{coins, from_binary, Coin, ContractResult, SystemResult, SubMsgResponse, attr};
+    use cosmwasm_std::{OwnedDeps, SystemError, WasmQuery};
+    use osmo_bindings::{ArithmeticTwapToNowResponse, Swap};
     use std::marker::PhantomData;
 
     pub fn mock_dependencies(
@@ -277,11 +622,43 @@ mod tests {
         }
     }
 
+    fn route() -> Vec<SwapAmountInRoute> {
+        vec![SwapAmountInRoute {
+            pool_id: 1,
+            token_out_denom: "out".to_string(),
+        }]
+    }
+
+    fn mock_current_execution(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier<OsmosisQuery>, OsmosisQuery>,
+        user: &str,
+    ) {
+        let execution = CurrentExecution {
+            id: 0,
+            user: user.to_string(),
+            target: MOCK_CONTRACT_ADDR.to_string(),
+            input_assets: vec![],
+        };
+        deps.querier.update_wasm(move |request| match request {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "registry" => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&CurrentExecutionResponse {
+                        execution: Some(execution.clone()),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => panic!("[mock]: unsupported wasm query {:?}", request),
+        });
+    }
+
     #[test]
     fn proper_instantialization() {
         let mut deps = mock_dependencies(&[]);
 
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg {
+            registry: "registry".to_string(),
+        };
         let info = mock_info("creator", &coins(1000, "earth"));
 
         // we can just call .unwrap() to assert this was a success
@@ -289,15 +666,36 @@ mod tests {
         assert_eq!(0, res.messages.len());
 
         // migrate
-        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg{}).unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { registry: None }).unwrap();
         assert_eq!(res, Response::default());
+
+        // migrate to another registry
+        migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                registry: Some("registry1".to_string()),
+            },
+        )
+        .unwrap();
+        let res: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(res.registry, "registry1");
+
+        // Registry should be set when migrating from a version without it
+        let mut deps = mock_dependencies(&[]);
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { registry: None }).err();
+        assert_eq!(err, Some(WrapperError::RegistryNotSet {}));
     }
 
     #[test]
     fn test_execute_swap() {
         let mut deps = mock_dependencies(&[]);
 
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg {
+            registry: "registry".to_string(),
+        };
         let info = mock_info("creator", &coins(1000, "earth"));
 
         // we can just call .unwrap() to assert this was a success
@@ -308,39 +706,61 @@ mod tests {
         // let first = Swap { pool_id: 1, denom_in: "in".to_string(), denom_out: "out".to_string() };
         let msg = ExecuteMsg::Swap {
             user: "addr0".to_string(),
-            route: vec![],
+            route: route(),
             denom_in: "earth".to_owned(),
             amount_in: Uint128::from(10u128),
             min_output: Uint128::from(10u128),
             max_output: Uint128::from(10u128),
             denom_out: "out".to_owned(),
         };
+
+        // Input should be attached
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).err();
+        assert_eq!(err, Some(WrapperError::InputNotReceived {}));
+
+        // Excess input would be left in the wrapper
+        let info = mock_info("creator", &coins(11, "earth"));
+        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).err();
+        assert_eq!(err, Some(WrapperError::InputNotReceived {}));
+
+        // Route should end in the output denom
+        let info = mock_info("creator", &coins(10, "earth"));
+        for route in [
+            vec![],
+            vec![SwapAmountInRoute {
+                pool_id: 1,
+                token_out_denom: "junk".to_string(),
+            }],
+        ] {
+            let msg = ExecuteMsg::Swap {
+                user: "addr0".to_string(),
+                route,
+                denom_in: "earth".to_owned(),
+                amount_in: Uint128::from(10u128),
+                min_output: Uint128::from(10u128),
+                max_output: Uint128::from(10u128),
+                denom_out: "out".to_owned(),
+            };
+            let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).err();
+            assert_eq!(err, Some(WrapperError::InvalidRoute {}));
+        }
+
+        let info = mock_info("creator", &coins(10, "earth"));
         let mock_env = mock_env();
         let contract_address = mock_env.contract.address.to_string();
         let res = execute(deps.as_mut(), mock_env, info, msg).unwrap();
         assert_eq!(
             res.messages,
             vec![
-                SubMsg::new(MsgSwapExactAmountIn{
+                SubMsg::reply_on_success(MsgSwapExactAmountIn{
                     sender: contract_address,
-                    routes: vec![],
+                    routes: route(),
                     token_in: Some(OsmoCoin {
                         denom: "earth".to_owned(),
                         amount: Uint128::from(10u128).to_string(),
                     }),
                     token_out_min_amount: Uint128::from(10u128).to_string()
-                }),
-                SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                    contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                    msg: to_binary(&ExecuteMsg::CheckRange {
-                        user: "addr0".to_string(),
-                        denom: "out".to_string(),
-                        balance_before: Uint128::zero(),
-                        min_output: Uint128::from(10u128),
-                        max_output: Uint128::from(10u128),
-                    }).unwrap(),
-                    funds: vec![],
-                }))
+                }, SWAP_REPLY_ID),
             ]
         );
         assert_eq!(
@@ -352,50 +772,375 @@ mod tests {
     }
 
     #[test]
-    fn test_check_range() {
-        let mut deps = mock_dependencies(&coins(100u128, "earth"));
+    fn test_registry_swap() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            registry: "registry".to_string(),
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::Swap {
+            user: "addr0".to_string(),
+            route: route(),
+            denom_in: "earth".to_owned(),
+            amount_in: Uint128::from(10u128),
+            min_output: Uint128::from(10u128),
+            max_output: Uint128::from(10u128),
+            denom_out: "out".to_owned(),
+        };
+        let info = mock_info("registry", &coins(10, "earth"));
+
+        // User should own the request being executed
+        mock_current_execution(&mut deps, "addr1");
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).err();
+        assert_eq!(err, Some(WrapperError::Common(CommonError::Unauthorized {})));
+
+        mock_current_execution(&mut deps, "addr0");
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    fn mock_osmosis_querier(twap: Decimal, spot_price: Decimal) -> MockQuerier<OsmosisQuery> {
+        MockQuerier::new(&[]).with_custom_handler(move |query| match query {
+            OsmosisQuery::SpotPrice {
+                swap,
+                with_swap_fee: false,
+            } if *swap == Swap::new(1, "earth", "out") => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&SpotPriceResponse { price: spot_price }).unwrap(),
+            )),
+            OsmosisQuery::ArithmeticTwapToNow {
+                id: 1,
+                quote_asset_denom,
+                base_asset_denom,
+                start_time,
+            } if quote_asset_denom == "out" && base_asset_denom == "earth" => {
+                // Window of 60 seconds up to now, in milliseconds
+                let now = mock_env().block.time.nanos() / 1_000_000;
+                assert_eq!(*start_time, (now - 60_000) as i64);
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&ArithmeticTwapToNowResponse { twap }).unwrap(),
+                ))
+            }
+            _ => panic!("[mock]: unsupported osmosis query {:?}", query),
+        })
+    }
+
+    #[test]
+    fn test_limit_swap() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InstantiateMsg {
+            registry: "registry".to_string(),
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = |twap_window: u64| ExecuteMsg::LimitSwap {
+            user: "addr0".to_string(),
+            route: route(),
+            denom_in: "earth".to_owned(),
+            amount_in: Uint128::from(10u128),
+            min_output: Uint128::from(15u128),
+            max_output: Uint128::from(100u128),
+            denom_out: "out".to_owned(),
+            pool_id: 1,
+            twap_window,
+            target_price: Decimal::from_ratio(3u128, 2u128),
+        };
+        let info = mock_info("creator", &coins(10, "earth"));
+
+        // Window should be given
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg(0)).err();
+        assert_eq!(
+            err,
+            Some(WrapperError::Common(CommonError::InvalidParam {
+                param_name: "twap_window".to_string(),
+                invalid_value: "0".to_string(),
+                predicate: "> 0".to_string(),
+            }))
+        );
+
+        // Window can't start before the zero timestamp
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg(u64::MAX)).err();
+        assert_eq!(
+            err,
+            Some(WrapperError::Common(CommonError::InvalidParam {
+                param_name: "twap_window".to_string(),
+                invalid_value: u64::MAX.to_string(),
+                predicate: format!("<= {}", mock_env().block.time.seconds()),
+            }))
+        );
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(mock_env().block.time.seconds() + 1),
+        )
+        .err();
+        assert_eq!(
+            err,
+            Some(WrapperError::Common(CommonError::InvalidParam {
+                param_name: "twap_window".to_string(),
+                invalid_value: (mock_env().block.time.seconds() + 1).to_string(),
+                predicate: format!("<= {}", mock_env().block.time.seconds()),
+            }))
+        );
+
+        // Not yet
+        deps.querier = mock_osmosis_querier(Decimal::from_ratio(7u128, 5u128), Decimal::one());
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg(60)).err();
+        assert_eq!(
+            err,
+            Some(WrapperError::PriceConditionNotMet {
+                twap: Decimal::from_ratio(7u128, 5u128),
+                target_price: Decimal::from_ratio(3u128, 2u128),
+            })
+        );
+
+        // Swaps once the TWAP reaches the target price
+        deps.querier = mock_osmosis_querier(Decimal::from_ratio(8u128, 5u128), Decimal::one());
+        let res = execute(deps.as_mut(), mock_env(), info, msg(60)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::reply_on_success(
+                MsgSwapExactAmountIn {
+                    sender: MOCK_CONTRACT_ADDR.to_string(),
+                    routes: route(),
+                    token_in: Some(OsmoCoin {
+                        denom: "earth".to_owned(),
+                        amount: "10".to_string(),
+                    }),
+                    token_out_min_amount: "15".to_string(),
+                },
+                SWAP_REPLY_ID
+            )]
+        );
+    }
+
+    #[test]
+    fn test_stop_loss() {
+        let mut deps = mock_dependencies(&[]);
 
-        let msg = InstantiateMsg {};
+        let msg = InstantiateMsg {
+            registry: "registry".to_string(),
+        };
+        let info = mock_info("creator", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = |twap_window: Option<u64>, max_slippage: Decimal, min_output: Option<u128>| {
+            ExecuteMsg::StopLoss {
+                user: "addr0".to_string(),
+                route: route(),
+                denom_in: "earth".to_owned(),
+                amount_in: Uint128::from(1000u128),
+                denom_out: "out".to_owned(),
+                pool_id: 1,
+                twap_window,
+                trigger_price: Decimal::from_ratio(4u128, 5u128),
+                max_slippage,
+                min_output: min_output.map(Uint128::from),
+            }
+        };
+        let swap_msg = |min_output: &str| {
+            SubMsg::reply_on_success(
+                MsgSwapExactAmountIn {
+                    sender: MOCK_CONTRACT_ADDR.to_string(),
+                    routes: route(),
+                    token_in: Some(OsmoCoin {
+                        denom: "earth".to_owned(),
+                        amount: "1000".to_string(),
+                    }),
+                    token_out_min_amount: min_output.to_string(),
+                },
+                SWAP_REPLY_ID,
+            )
+        };
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let slippage = Decimal::percent(2);
+
+        // Slippage can't exceed the output
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(Some(60), Decimal::percent(101), None),
+        )
+        .err();
+        assert_eq!(
+            err,
+            Some(WrapperError::Common(CommonError::InvalidParam {
+                param_name: "max_slippage".to_string(),
+                invalid_value: "1.01".to_string(),
+                predicate: "<= 1".to_string(),
+            }))
+        );
+
+        // Spot price is only used with a minimum output
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(None, slippage, None),
+        )
+        .err();
+        assert_eq!(
+            err,
+            Some(WrapperError::Common(CommonError::InvalidParam {
+                param_name: "min_output".to_string(),
+                invalid_value: "None".to_string(),
+                predicate: "set if twap_window is not set".to_string(),
+            }))
+        );
+
+        // Spot price above the trigger price
+        deps.querier = mock_osmosis_querier(Decimal::percent(70), Decimal::percent(90));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(None, slippage, Some(750)),
+        )
+        .err();
+        assert_eq!(
+            err,
+            Some(WrapperError::StopNotTriggered {
+                price: Decimal::percent(90),
+                trigger_price: Decimal::percent(80),
+            })
+        );
+
+        // TWAP below the trigger price, output is bounded by the slippage from the trigger price
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(Some(60), slippage, None),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![swap_msg("784")]);
+
+        // Minimum output given by the user
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(Some(60), slippage, Some(650)),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![swap_msg("650")]);
+
+        // TWAP above the trigger price
+        deps.querier = mock_osmosis_querier(Decimal::percent(85), Decimal::percent(75));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(Some(60), slippage, None),
+        )
+        .err();
+        assert_eq!(
+            err,
+            Some(WrapperError::StopNotTriggered {
+                price: Decimal::percent(85),
+                trigger_price: Decimal::percent(80),
+            })
+        );
+
+        // Spot price below the trigger price
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            msg(None, slippage, Some(750)),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![swap_msg("750")]);
+
+        // Spot price manipulated far below the trigger price doesn't lower the minimum output
+        deps.querier = mock_osmosis_querier(Decimal::percent(85), Decimal::percent(10));
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            msg(None, slippage, Some(750)),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![swap_msg("750")]);
+    }
+
+    #[test]
+    fn test_swap_reply() {
+        // Balance held by the wrapper is not part of the output
+        let mut deps = mock_dependencies(&coins(100u128, "out"));
+
+        let msg = InstantiateMsg {
+            registry: "registry".to_string(),
+        };
         let info = mock_info("creator", &coins(1000, "earth"));
 
         // we can just call .unwrap() to assert this was a success
         let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
         assert_eq!(0, res.messages.len());
 
-        let info = mock_info("creator", &[]);
-        let msg = ExecuteMsg::CheckRange {
-            user: "addr0".to_string(),
-            denom: "earth".to_string(),
-            balance_before: Uint128::zero(),
-            min_output: Uint128::from(10u128),
-            max_output: Uint128::from(10u128),
+        let swap = |deps: DepsMut<OsmosisQuery>| {
+            let msg = ExecuteMsg::Swap {
+                user: "addr0".to_string(),
+                route: route(),
+                denom_in: "earth".to_owned(),
+                amount_in: Uint128::from(10u128),
+                min_output: Uint128::from(10u128),
+                max_output: Uint128::from(20u128),
+                denom_out: "out".to_owned(),
+            };
+            execute(deps, mock_env(), mock_info("creator", &coins(10, "earth")), msg).unwrap();
+        };
+        let swap_reply = |deps: DepsMut<OsmosisQuery>, token_out_amount: &str| {
+            reply(
+                deps,
+                mock_env(),
+                Reply {
+                    id: SWAP_REPLY_ID,
+                    result: SubMsgResult::Ok(SubMsgResponse {
+                        events: vec![],
+                        data: Some(
+                            MsgSwapExactAmountInResponse {
+                                token_out_amount: token_out_amount.to_string(),
+                            }
+                            .into(),
+                        ),
+                    }),
+                },
+            )
         };
 
-        // NotWrapperContract
-        let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).err();
-        assert_eq!(err, Some(WrapperError::NotWrapperContract { expected: MOCK_CONTRACT_ADDR.to_string(), actual: "creator".to_string() }));
+        // Unknown reply id
+        let err = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: 2,
+                result: SubMsgResult::Err("error".to_string()),
+            },
+        )
+        .err();
+        assert_eq!(err, Some(WrapperError::Common(CommonError::Unauthorized {})));
 
         // InvalidOutput
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let err = execute(deps.as_mut(), mock_env(), info, msg).err();
-        assert_eq!(err, Some(WrapperError::InvalidOutput { expected_min: Uint128::from(10u128), expected_max: Uint128::from(10u128), actual: Uint128::from(100u128) }));
+        swap(deps.as_mut());
+        let err = swap_reply(deps.as_mut(), "21").err();
+        assert_eq!(err, Some(WrapperError::InvalidOutput { expected_min: Uint128::from(10u128), expected_max: Uint128::from(20u128), actual: Uint128::from(21u128) }));
 
-        let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
-        let msg = ExecuteMsg::CheckRange {
-            user: "addr0".to_string(),
-            denom: "earth".to_string(),
-            balance_before: Uint128::zero(),
-            min_output: Uint128::from(99u128),
-            max_output: Uint128::from(101u128),
-        };
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        swap(deps.as_mut());
+        let res = swap_reply(deps.as_mut(), "15").unwrap();
         assert_eq!(
             res.messages,
             vec![
                 SubMsg::new(
                     CosmosMsg::Bank(BankMsg::Send {
                         to_address: "addr0".to_string(),
-                        amount: coins(100u128, "earth"),
+                        amount: coins(15u128, "out"),
                     })
                 )
             ]
@@ -403,7 +1148,8 @@ mod tests {
         assert_eq!(
             res.attributes,
             vec![
-                attr("action", "execute_check_range")
+                attr("action", "finalize_swap"),
+                attr("output", "15"),
             ]
         )
     }
@@ -1,4 +1,5 @@
-use cosmwasm_std::{StdError, Uint128};
+use autonomy::error::CommonError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,11 +7,32 @@ pub enum WrapperError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Common(#[from] CommonError),
+
     #[error("Semver parsing error: {0}")]
     SemVer(String),
 
-    #[error("Permission denied: the sender must be the wrapper")]
-    NotWrapperContract { expected: String, actual: String },
+    #[error("Exactly the input should be attached to the call")]
+    InputNotReceived {},
+
+    #[error("Registry should be set when migrating from a version without it")]
+    RegistryNotSet {},
+
+    #[error("Route should end in the output denom")]
+    InvalidRoute {},
+
+    #[error("Price condition not met: TWAP is {twap}, target price is {target_price}")]
+    PriceConditionNotMet {
+        twap: Decimal,
+        target_price: Decimal,
+    },
+
+    #[error("Stop not triggered: price is {price}, trigger price is {trigger_price}")]
+    StopNotTriggered {
+        price: Decimal,
+        trigger_price: Decimal,
+    },
 
     #[error("Invalid output amount")]
     InvalidOutput {
@@ -1,3 +1,4 @@
 pub mod contract;
 mod error;
 pub mod msg;
+pub mod state;
@@ -2,13 +2,19 @@ use osmosis_std::types::osmosis::gamm::v1beta1::{SwapAmountInRoute};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    // Registry allowed to call the wrapper on behalf of its users
+    pub registry: String,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    // Registry to set, required when migrating from a version without it
+    pub registry: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -29,16 +35,60 @@ pub enum ExecuteMsg {
         // Out denom
         denom_out: String,
     },
-    CheckRange {
+    LimitSwap {
         // Address of the user for this swap
         user: String,
-        // Denom of the output asset
-        denom: String,
-        // Balance before this swap
-        balance_before: Uint128,
+        // Swap routes
+        route: Vec<SwapAmountInRoute>,
+        // Input denom
+        denom_in: String,
+        // Input amount
+        amount_in: Uint128,
         // Minimum output amount
         min_output: Uint128,
         // Maximum output amount
         max_output: Uint128,
+        // Out denom
+        denom_out: String,
+        // Pool to read the TWAP of `denom_in` quoted in `denom_out` from
+        pool_id: u64,
+        // TWAP window up to now, in seconds
+        twap_window: u64,
+        // Swap only when the TWAP is at least this price
+        target_price: Decimal,
+    },
+    StopLoss {
+        // Address of the user for this swap
+        user: String,
+        // Swap routes
+        route: Vec<SwapAmountInRoute>,
+        // Input denom
+        denom_in: String,
+        // Input amount
+        amount_in: Uint128,
+        // Out denom
+        denom_out: String,
+        // Pool to read the price of `denom_in` quoted in `denom_out` from
+        pool_id: u64,
+        // TWAP window up to now in seconds, spot price is used if not set
+        twap_window: Option<u64>,
+        // Swap only when the price is at most this price
+        trigger_price: Decimal,
+        // Maximum slippage of the output from the trigger price
+        max_slippage: Decimal,
+        // Minimum output amount instead of the one from the trigger price, required for the spot price
+        min_output: Option<Uint128>,
     },
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    // Registry allowed to call the wrapper on behalf of its users
+    pub registry: String,
+}
@@ -0,0 +1,31 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Uint128};
+use cw_storage_plus::Item;
+
+/// ## Description
+/// This structure describes the main control config of the wrapper.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Config {
+    /// Registry allowed to call the wrapper on behalf of its users
+    pub registry: Addr,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// ## Description
+/// This structure describes the swap waiting for its output in the reply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PendingSwap {
+    /// Address that receives the output
+    pub user: Addr,
+    /// Denom of the output asset
+    pub denom_out: String,
+    /// Minimum output amount
+    pub min_output: Uint128,
+    /// Maximum output amount
+    pub max_output: Uint128,
+}
+
+pub const PENDING_SWAP: Item<PendingSwap> = Item::new("pending_swap");
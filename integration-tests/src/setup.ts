@@ -179,7 +179,9 @@ async function setup(
     wallet1,
     wallet1,
     wrapperOsmosisCodeId,
-    {}
+    {
+      registry,
+    }
   );
   wrapperOsmosis = wrapperOsmosisResult.contractAddress;
   console.log(
@@ -58,6 +58,9 @@ async function testStaking(
 
   const config = await registryClient1.config();
   const state0 = await registryClient1.state();
+  const stake0 = await registryClient1.stakeAmount({
+    user: localosmosis.addresses.wallet1,
+  });
 
   // Stake asset
   const numStakes = 12;
@@ -81,7 +84,12 @@ async function testStaking(
   );
 
   const state1 = await registryClient1.state();
-  expect(state0.stakes_len + numStakes).to.be.equal(state1.stakes_len);
+  const stake1 = await registryClient1.stakeAmount({
+    user: localosmosis.addresses.wallet1,
+  });
+  expect(parseInt(stake0.amount) + stakeAmount).to.be.equal(
+    parseInt(stake1.amount)
+  );
   expect(parseInt(state0.total_stake_amount) + stakeAmount).to.be.equal(
     parseInt(state1.total_stake_amount)
   );
@@ -90,7 +98,7 @@ async function testStaking(
   const unstakeCount = 3;
   const unstakeAmount = parseInt(config.stake_amount) * unstakeCount;
   const txnRes1 = await registryClient1.unstake({
-    idxs: [state0.stakes_len, state0.stakes_len, state0.stakes_len],
+    numStakes: unstakeCount,
   });
   const accBal2 = await client.getBalance(localosmosis.addresses.wallet1, auto);
   const conBal2 = await client.getBalance(registry, auto);
@@ -101,7 +109,12 @@ async function testStaking(
   );
 
   const state2 = await registryClient1.state();
-  expect(state1.stakes_len - unstakeCount).to.be.equal(state2.stakes_len);
+  const stake2 = await registryClient1.stakeAmount({
+    user: localosmosis.addresses.wallet1,
+  });
+  expect(parseInt(stake1.amount) - unstakeAmount).to.be.equal(
+    parseInt(stake2.amount)
+  );
   expect(parseInt(state1.total_stake_amount) - unstakeAmount).to.be.equal(
     parseInt(state2.total_stake_amount)
   );
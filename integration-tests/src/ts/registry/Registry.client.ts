@@ -6,10 +6,11 @@
 
 import { CosmWasmClient, SigningCosmWasmClient, ExecuteResult } from "@cosmjs/cosmwasm-stargate";
 import { Coin, StdFee } from "@cosmjs/amino";
-import { AssetInfo, Addr, Uint128, Config, CreateOrUpdateConfig, Binary, CreateRequestInfo, Asset, EpochInfoResponse, ExecuteMsg, Cw20ReceiveMsg, InstantiateMsg, QueryMsg, OrderBy, RecurringFeeAmountResponse, RequestInfoResponse, Request, RequestsResponse, StakeAmountResponse, StakesResponse, StateResponse, State, AdminResponse } from "./Registry.types";
+import { AdminResponse, BlacklistResponse, Config, AssetInfo, Addr, Uint128, RandomnessSource, Duration, CreateOrUpdateConfig, CreateRequestInfo, Expiration, Timestamp, Uint64, Asset, Binary, RecurringSchedule, Scheduled, CurrentExecutionResponse, CurrentExecution, EpochInfoResponse, ExecuteMsg, UpdateRequestInfo, Cw20ReceiveMsg, InstantiateMsg, PendingRewardsResponse, Coin, QueryMsg, OrderBy, RecurringFeeAmountResponse, RequestExecutionsResponse, RequestExecution, RequestInfoResponse, Request, RequestStatus, RequestsResponse, StakeAmountResponse, StakesResponse, State, StateResponse, TreasuryResponse, UnbondingResponse, UnbondingEntry } from "./Registry.types";
 export interface RegistryReadOnlyInterface {
   contractAddress: string;
   admin: () => Promise<AdminResponse>;
+  pendingAdmin: () => Promise<AdminResponse>;
   config: () => Promise<Config>;
   state: () => Promise<StateResponse>;
   recurringFees: ({
@@ -31,12 +32,42 @@ export interface RegistryReadOnlyInterface {
     orderBy?: OrderBy;
     startAfter?: number;
   }) => Promise<RequestsResponse>;
+  requestsByUser: ({
+    limit,
+    startAfter,
+    user
+  }: {
+    limit?: number;
+    startAfter?: number;
+    user: string;
+  }) => Promise<RequestsResponse>;
+  requestsByTarget: ({
+    limit,
+    startAfter,
+    target
+  }: {
+    limit?: number;
+    startAfter?: number;
+    target: string;
+  }) => Promise<RequestsResponse>;
   epochInfo: () => Promise<EpochInfoResponse>;
   stakeAmount: ({
     user
   }: {
     user: string;
   }) => Promise<StakeAmountResponse>;
+  unbonding: ({
+    user
+  }: {
+    user: string;
+  }) => Promise<UnbondingResponse>;
+  pendingRewards: ({
+    user
+  }: {
+    user: string;
+  }) => Promise<PendingRewardsResponse>;
+  treasury: () => Promise<TreasuryResponse>;
+  currentExecution: () => Promise<CurrentExecutionResponse>;
   stakes: ({
     limit,
     start
@@ -44,6 +75,16 @@ export interface RegistryReadOnlyInterface {
     limit: number;
     start: number;
   }) => Promise<StakesResponse>;
+  blacklist: () => Promise<BlacklistResponse>;
+  requestExecutions: ({
+    id,
+    limit,
+    startAfter
+  }: {
+    id: number;
+    limit?: number;
+    startAfter?: number;
+  }) => Promise<RequestExecutionsResponse>;
 }
 export class RegistryQueryClient implements RegistryReadOnlyInterface {
   client: CosmWasmClient;
@@ -53,14 +94,23 @@ export class RegistryQueryClient implements RegistryReadOnlyInterface {
     this.client = client;
     this.contractAddress = contractAddress;
     this.admin = this.admin.bind(this);
+    this.pendingAdmin = this.pendingAdmin.bind(this);
     this.config = this.config.bind(this);
     this.state = this.state.bind(this);
     this.recurringFees = this.recurringFees.bind(this);
     this.requestInfo = this.requestInfo.bind(this);
     this.requests = this.requests.bind(this);
+    this.requestsByUser = this.requestsByUser.bind(this);
+    this.requestsByTarget = this.requestsByTarget.bind(this);
     this.epochInfo = this.epochInfo.bind(this);
     this.stakeAmount = this.stakeAmount.bind(this);
+    this.unbonding = this.unbonding.bind(this);
+    this.pendingRewards = this.pendingRewards.bind(this);
+    this.treasury = this.treasury.bind(this);
+    this.currentExecution = this.currentExecution.bind(this);
     this.stakes = this.stakes.bind(this);
+    this.blacklist = this.blacklist.bind(this);
+    this.requestExecutions = this.requestExecutions.bind(this);
   }
 
   admin = async (): Promise<AdminResponse> => {
@@ -68,6 +118,11 @@ export class RegistryQueryClient implements RegistryReadOnlyInterface {
       admin: {}
     });
   };
+  pendingAdmin = async (): Promise<AdminResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      pending_admin: {}
+    });
+  };
   config = async (): Promise<Config> => {
     return this.client.queryContractSmart(this.contractAddress, {
       config: {}
@@ -117,6 +172,40 @@ export class RegistryQueryClient implements RegistryReadOnlyInterface {
       }
     });
   };
+  requestsByUser = async ({
+    limit,
+    startAfter,
+    user
+  }: {
+    limit?: number;
+    startAfter?: number;
+    user: string;
+  }): Promise<RequestsResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      requests_by_user: {
+        limit,
+        start_after: startAfter,
+        user
+      }
+    });
+  };
+  requestsByTarget = async ({
+    limit,
+    startAfter,
+    target
+  }: {
+    limit?: number;
+    startAfter?: number;
+    target: string;
+  }): Promise<RequestsResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      requests_by_target: {
+        limit,
+        start_after: startAfter,
+        target
+      }
+    });
+  };
   epochInfo = async (): Promise<EpochInfoResponse> => {
     return this.client.queryContractSmart(this.contractAddress, {
       epoch_info: {}
@@ -133,6 +222,38 @@ export class RegistryQueryClient implements RegistryReadOnlyInterface {
       }
     });
   };
+  unbonding = async ({
+    user
+  }: {
+    user: string;
+  }): Promise<UnbondingResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      unbonding: {
+        user
+      }
+    });
+  };
+  pendingRewards = async ({
+    user
+  }: {
+    user: string;
+  }): Promise<PendingRewardsResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      pending_rewards: {
+        user
+      }
+    });
+  };
+  treasury = async (): Promise<TreasuryResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      treasury: {}
+    });
+  };
+  currentExecution = async (): Promise<CurrentExecutionResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      current_execution: {}
+    });
+  };
   stakes = async ({
     limit,
     start
@@ -147,6 +268,28 @@ export class RegistryQueryClient implements RegistryReadOnlyInterface {
       }
     });
   };
+  blacklist = async (): Promise<BlacklistResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      blacklist: {}
+    });
+  };
+  requestExecutions = async ({
+    id,
+    limit,
+    startAfter
+  }: {
+    id: number;
+    limit?: number;
+    startAfter?: number;
+  }): Promise<RequestExecutionsResponse> => {
+    return this.client.queryContractSmart(this.contractAddress, {
+      request_executions: {
+        id,
+        limit,
+        start_after: startAfter
+      }
+    });
+  };
 }
 export interface RegistryInterface extends RegistryReadOnlyInterface {
   contractAddress: string;
@@ -162,6 +305,32 @@ export interface RegistryInterface extends RegistryReadOnlyInterface {
   }: {
     requestInfo: CreateRequestInfo;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  updateRequest: ({
+    id,
+    requestInfo
+  }: {
+    id: number;
+    requestInfo: UpdateRequestInfo;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  transferRequest: ({
+    id,
+    newOwner,
+    requireAccept
+  }: {
+    id: number;
+    newOwner: string;
+    requireAccept?: boolean;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  acceptRequestTransfer: ({
+    id
+  }: {
+    id: number;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  cancelRequestTransfer: ({
+    id
+  }: {
+    id: number;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   cancelRequest: ({
     id
   }: {
@@ -172,6 +341,14 @@ export interface RegistryInterface extends RegistryReadOnlyInterface {
   }: {
     id: number;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  executeRequests: ({
+    ids,
+    skipFailed
+  }: {
+    ids: number[];
+    skipFailed: boolean;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  forwardInputAssets: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   depositRecurringFee: ({
     recurringCount
   }: {
@@ -197,11 +374,34 @@ export interface RegistryInterface extends RegistryReadOnlyInterface {
     numStakes: number;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   unstake: ({
-    idxs
+    numStakes
   }: {
-    idxs: number[];
+    numStakes: number;
   }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  withdrawUnbonded: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  reportPendingRequest: ({
+    id
+  }: {
+    id: number;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  reportMissedEpoch: ({
+    epoch
+  }: {
+    epoch: number;
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  claimRewards: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  withdrawTreasury: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
   updateExecutor: (fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  addToBlacklist: ({
+    addrs
+  }: {
+    addrs: string[];
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
+  removeFromBlacklist: ({
+    addrs
+  }: {
+    addrs: string[];
+  }, fee?: number | StdFee | "auto", memo?: string, funds?: Coin[]) => Promise<ExecuteResult>;
 }
 export class RegistryClient extends RegistryQueryClient implements RegistryInterface {
   client: SigningCosmWasmClient;
@@ -216,14 +416,27 @@ export class RegistryClient extends RegistryQueryClient implements RegistryInter
     this.claimAdmin = this.claimAdmin.bind(this);
     this.updateConfig = this.updateConfig.bind(this);
     this.createRequest = this.createRequest.bind(this);
+    this.updateRequest = this.updateRequest.bind(this);
+    this.transferRequest = this.transferRequest.bind(this);
+    this.acceptRequestTransfer = this.acceptRequestTransfer.bind(this);
+    this.cancelRequestTransfer = this.cancelRequestTransfer.bind(this);
     this.cancelRequest = this.cancelRequest.bind(this);
     this.executeRequest = this.executeRequest.bind(this);
+    this.executeRequests = this.executeRequests.bind(this);
+    this.forwardInputAssets = this.forwardInputAssets.bind(this);
     this.depositRecurringFee = this.depositRecurringFee.bind(this);
     this.withdrawRecurringFee = this.withdrawRecurringFee.bind(this);
     this.receive = this.receive.bind(this);
     this.stakeDenom = this.stakeDenom.bind(this);
     this.unstake = this.unstake.bind(this);
+    this.withdrawUnbonded = this.withdrawUnbonded.bind(this);
+    this.reportPendingRequest = this.reportPendingRequest.bind(this);
+    this.reportMissedEpoch = this.reportMissedEpoch.bind(this);
+    this.claimRewards = this.claimRewards.bind(this);
+    this.withdrawTreasury = this.withdrawTreasury.bind(this);
     this.updateExecutor = this.updateExecutor.bind(this);
+    this.addToBlacklist = this.addToBlacklist.bind(this);
+    this.removeFromBlacklist = this.removeFromBlacklist.bind(this);
   }
 
   claimAdmin = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
@@ -253,6 +466,59 @@ export class RegistryClient extends RegistryQueryClient implements RegistryInter
       }
     }, fee, memo, funds);
   };
+  updateRequest = async ({
+    id,
+    requestInfo
+  }: {
+    id: number;
+    requestInfo: UpdateRequestInfo;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      update_request: {
+        id,
+        request_info: requestInfo
+      }
+    }, fee, memo, funds);
+  };
+  transferRequest = async ({
+    id,
+    newOwner,
+    requireAccept
+  }: {
+    id: number;
+    newOwner: string;
+    requireAccept?: boolean;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      transfer_request: {
+        id,
+        new_owner: newOwner,
+        require_accept: requireAccept
+      }
+    }, fee, memo, funds);
+  };
+  acceptRequestTransfer = async ({
+    id
+  }: {
+    id: number;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      accept_request_transfer: {
+        id
+      }
+    }, fee, memo, funds);
+  };
+  cancelRequestTransfer = async ({
+    id
+  }: {
+    id: number;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      cancel_request_transfer: {
+        id
+      }
+    }, fee, memo, funds);
+  };
   cancelRequest = async ({
     id
   }: {
@@ -275,6 +541,25 @@ export class RegistryClient extends RegistryQueryClient implements RegistryInter
       }
     }, fee, memo, funds);
   };
+  executeRequests = async ({
+    ids,
+    skipFailed
+  }: {
+    ids: number[];
+    skipFailed: boolean;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      execute_requests: {
+        ids,
+        skip_failed: skipFailed
+      }
+    }, fee, memo, funds);
+  };
+  forwardInputAssets = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      forward_input_assets: {}
+    }, fee, memo, funds);
+  };
   depositRecurringFee = async ({
     recurringCount
   }: {
@@ -326,19 +611,78 @@ export class RegistryClient extends RegistryQueryClient implements RegistryInter
     }, fee, memo, funds);
   };
   unstake = async ({
-    idxs
+    numStakes
   }: {
-    idxs: number[];
+    numStakes: number;
   }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
       unstake: {
-        idxs
+        num_stakes: numStakes
       }
     }, fee, memo, funds);
   };
+  withdrawUnbonded = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      withdraw_unbonded: {}
+    }, fee, memo, funds);
+  };
+  reportPendingRequest = async ({
+    id
+  }: {
+    id: number;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      report_pending_request: {
+        id
+      }
+    }, fee, memo, funds);
+  };
+  reportMissedEpoch = async ({
+    epoch
+  }: {
+    epoch: number;
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      report_missed_epoch: {
+        epoch
+      }
+    }, fee, memo, funds);
+  };
+  claimRewards = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      claim_rewards: {}
+    }, fee, memo, funds);
+  };
+  withdrawTreasury = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      withdraw_treasury: {}
+    }, fee, memo, funds);
+  };
   updateExecutor = async (fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
     return await this.client.execute(this.sender, this.contractAddress, {
       update_executor: {}
     }, fee, memo, funds);
   };
-}
+  addToBlacklist = async ({
+    addrs
+  }: {
+    addrs: string[];
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      add_to_blacklist: {
+        addrs
+      }
+    }, fee, memo, funds);
+  };
+  removeFromBlacklist = async ({
+    addrs
+  }: {
+    addrs: string[];
+  }, fee: number | StdFee | "auto" = "auto", memo?: string, funds?: Coin[]): Promise<ExecuteResult> => {
+    return await this.client.execute(this.sender, this.contractAddress, {
+      remove_from_blacklist: {
+        addrs
+      }
+    }, fee, memo, funds);
+  };
+}
\ No newline at end of file
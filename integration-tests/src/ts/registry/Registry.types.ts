@@ -4,6 +4,14 @@
 * and run the @cosmwasm/ts-codegen generate command to regenerate this file.
 */
 
+export interface AdminResponse {
+  admin?: string | null;
+  [k: string]: unknown;
+}
+export interface BlacklistResponse {
+  blacklist: string[];
+  [k: string]: unknown;
+}
 export type AssetInfo = {
   token: {
     contract_addr: Addr;
@@ -17,29 +25,81 @@ export type AssetInfo = {
 };
 export type Addr = string;
 export type Uint128 = string;
+export type RandomnessSource = "block_height" | "hash_chain";
+export type Duration = {
+  height: number;
+} | {
+  time: number;
+};
 export interface Config {
   auto: AssetInfo;
   blocks_in_epoch: number;
+  expiry_bounty_bps?: number;
   fee_amount: Uint128;
   fee_denom: string;
+  hook_tokens?: Addr[];
+  open_grace_blocks?: number | null;
+  protocol_fee_bps?: number;
+  randomness_source?: RandomnessSource;
+  slash_bounty_bps?: number;
+  slash_bps?: number;
+  slash_notice_blocks?: number;
   stake_amount: Uint128;
+  staker_fee_bps?: number;
+  staker_grace_blocks?: number | null;
+  treasury?: Addr | null;
+  unbonding_period?: Duration | null;
   [k: string]: unknown;
 }
 export interface CreateOrUpdateConfig {
   admin?: string | null;
   auto?: AssetInfo | null;
   blocks_in_epoch?: number | null;
+  clear_grace_blocks?: boolean | null;
+  clear_treasury?: boolean | null;
+  clear_unbonding_period?: boolean | null;
+  expiry_bounty_bps?: number | null;
   fee_amount?: Uint128 | null;
   fee_denom?: string | null;
+  hook_tokens?: string[] | null;
+  open_grace_blocks?: number | null;
+  protocol_fee_bps?: number | null;
+  randomness_source?: RandomnessSource | null;
+  slash_bounty_bps?: number | null;
+  slash_bps?: number | null;
+  slash_notice_blocks?: number | null;
   stake_amount?: Uint128 | null;
+  staker_fee_bps?: number | null;
+  staker_grace_blocks?: number | null;
+  treasury?: string | null;
+  unbonding_period?: Duration | null;
   [k: string]: unknown;
 }
+export type Expiration = {
+  at_height: number;
+} | {
+  at_time: Timestamp;
+} | {
+  never: {};
+};
+export type Timestamp = Uint64;
+export type Uint64 = string;
 export type Binary = string;
+export type Scheduled = {
+  at_height: number;
+} | {
+  at_time: Timestamp;
+};
 export interface CreateRequestInfo {
+  catch_failure?: boolean | null;
+  expires_at?: Expiration | null;
   input_asset?: Asset | null;
+  input_assets?: Asset[];
   is_recurring: boolean;
   msg: Binary;
+  recurring_schedule?: RecurringSchedule | null;
   target: string;
+  valid_from?: Scheduled | null;
   [k: string]: unknown;
 }
 export interface Asset {
@@ -47,6 +107,22 @@ export interface Asset {
   info: AssetInfo;
   [k: string]: unknown;
 }
+export interface RecurringSchedule {
+  interval: Duration;
+  max_executions?: number | null;
+  [k: string]: unknown;
+}
+export interface CurrentExecutionResponse {
+  execution?: CurrentExecution | null;
+  [k: string]: unknown;
+}
+export interface CurrentExecution {
+  id: number;
+  input_assets: Asset[];
+  target: string;
+  user: string;
+  [k: string]: unknown;
+}
 export interface EpochInfoResponse {
   cur_epoch: number;
   executor: string;
@@ -67,6 +143,29 @@ export type ExecuteMsg = {
     request_info: CreateRequestInfo;
     [k: string]: unknown;
   };
+} | {
+  update_request: {
+    id: number;
+    request_info: UpdateRequestInfo;
+    [k: string]: unknown;
+  };
+} | {
+  transfer_request: {
+    id: number;
+    new_owner: string;
+    require_accept?: boolean | null;
+    [k: string]: unknown;
+  };
+} | {
+  accept_request_transfer: {
+    id: number;
+    [k: string]: unknown;
+  };
+} | {
+  cancel_request_transfer: {
+    id: number;
+    [k: string]: unknown;
+  };
 } | {
   cancel_request: {
     id: number;
@@ -77,6 +176,16 @@ export type ExecuteMsg = {
     id: number;
     [k: string]: unknown;
   };
+} | {
+  execute_requests: {
+    ids: number[];
+    skip_failed: boolean;
+    [k: string]: unknown;
+  };
+} | {
+  forward_input_assets: {
+    [k: string]: unknown;
+  };
 } | {
   deposit_recurring_fee: {
     recurring_count: number;
@@ -96,14 +205,52 @@ export type ExecuteMsg = {
   };
 } | {
   unstake: {
-    idxs: number[];
+    num_stakes: number;
+    [k: string]: unknown;
+  };
+} | {
+  withdraw_unbonded: {
+    [k: string]: unknown;
+  };
+} | {
+  report_pending_request: {
+    id: number;
+    [k: string]: unknown;
+  };
+} | {
+  report_missed_epoch: {
+    epoch: number;
+    [k: string]: unknown;
+  };
+} | {
+  claim_rewards: {
+    [k: string]: unknown;
+  };
+} | {
+  withdraw_treasury: {
     [k: string]: unknown;
   };
 } | {
   update_executor: {
     [k: string]: unknown;
   };
+} | {
+  add_to_blacklist: {
+    addrs: string[];
+    [k: string]: unknown;
+  };
+} | {
+  remove_from_blacklist: {
+    addrs: string[];
+    [k: string]: unknown;
+  };
 };
+export interface UpdateRequestInfo {
+  input_assets?: Asset[] | null;
+  msg?: Binary | null;
+  recurring_schedule?: RecurringSchedule | null;
+  [k: string]: unknown;
+}
 export interface Cw20ReceiveMsg {
   amount: Uint128;
   msg: Binary;
@@ -113,10 +260,23 @@ export interface InstantiateMsg {
   config: CreateOrUpdateConfig;
   [k: string]: unknown;
 }
+export interface PendingRewardsResponse {
+  rewards: Coin[];
+  [k: string]: unknown;
+}
+export interface Coin {
+  amount: Uint128;
+  denom: string;
+  [k: string]: unknown;
+}
 export type QueryMsg = {
   admin: {
     [k: string]: unknown;
   };
+} | {
+  pending_admin: {
+    [k: string]: unknown;
+  };
 } | {
   config: {
     [k: string]: unknown;
@@ -142,6 +302,20 @@ export type QueryMsg = {
     start_after?: number | null;
     [k: string]: unknown;
   };
+} | {
+  requests_by_user: {
+    limit?: number | null;
+    start_after?: number | null;
+    user: string;
+    [k: string]: unknown;
+  };
+} | {
+  requests_by_target: {
+    limit?: number | null;
+    start_after?: number | null;
+    target: string;
+    [k: string]: unknown;
+  };
 } | {
   epoch_info: {
     [k: string]: unknown;
@@ -151,34 +325,82 @@ export type QueryMsg = {
     user: string;
     [k: string]: unknown;
   };
+} | {
+  unbonding: {
+    user: string;
+    [k: string]: unknown;
+  };
+} | {
+  pending_rewards: {
+    user: string;
+    [k: string]: unknown;
+  };
+} | {
+  treasury: {
+    [k: string]: unknown;
+  };
+} | {
+  current_execution: {
+    [k: string]: unknown;
+  };
 } | {
   stakes: {
     limit: number;
     start: number;
     [k: string]: unknown;
   };
+} | {
+  blacklist: {
+    [k: string]: unknown;
+  };
+} | {
+  request_executions: {
+    id: number;
+    limit?: number | null;
+    start_after?: number | null;
+    [k: string]: unknown;
+  };
 };
 export type OrderBy = "asc" | "desc";
-export interface AdminResponse {
-  admin: string;
-  [k: string]: unknown;
-}
 export interface RecurringFeeAmountResponse {
   amount: Uint128;
   [k: string]: unknown;
 }
+export interface RequestExecutionsResponse {
+  executions: RequestExecution[];
+  id: number;
+  [k: string]: unknown;
+}
+export interface RequestExecution {
+  error?: string | null;
+  executed_at: number;
+  execution: number;
+  executor: string;
+  success: boolean;
+  [k: string]: unknown;
+}
+export type RequestStatus = "pending" | "failed";
 export interface RequestInfoResponse {
   id: number;
   request: Request;
   [k: string]: unknown;
 }
 export interface Request {
+  catch_failure?: boolean;
   created_at: number;
-  input_asset?: Asset | null;
+  executions_done?: number;
+  expires_at?: Expiration | null;
+  fee?: Asset | null;
+  input_assets?: Asset[];
   is_recurring: boolean;
+  last_executed_at?: Scheduled | null;
   msg: Binary;
+  pending_owner?: string | null;
+  recurring_schedule?: RecurringSchedule | null;
+  status?: RequestStatus;
   target: string;
   user: string;
+  valid_from?: Scheduled | null;
   [k: string]: unknown;
 }
 export interface RequestsResponse {
@@ -193,23 +415,39 @@ export interface StakesResponse {
   stakes: string[];
   [k: string]: unknown;
 }
-export interface StateResponse {
+export interface State {
   curr_executing_request_id: number;
+  entropy?: Binary;
+  executor: string;
+  last_epoch: number;
   next_request_id: number;
-  stakes_len: number;
+  num_stakers?: number;
   total_recurring_fee: Uint128;
   total_requests: number;
-  total_stake_amount: Uint128;
+  total_staked: Uint128;
   [k: string]: unknown;
 }
-export interface State {
+export interface StateResponse {
   curr_executing_request_id: number;
-  executor: string;
-  last_epoch: number;
   next_request_id: number;
-  stakes: string[];
+  num_stakers: number;
   total_recurring_fee: Uint128;
   total_requests: number;
-  total_staked: Uint128;
+  total_stake_amount: Uint128;
+  [k: string]: unknown;
+}
+export interface TreasuryResponse {
+  balance: Coin[];
+  treasury?: string | null;
+  withdrawn: Coin[];
   [k: string]: unknown;
 }
+export interface UnbondingResponse {
+  entries: UnbondingEntry[];
+  [k: string]: unknown;
+}
+export interface UnbondingEntry {
+  amount: Uint128;
+  release_at: Expiration;
+  [k: string]: unknown;
+}
\ No newline at end of file
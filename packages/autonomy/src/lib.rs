@@ -2,6 +2,7 @@ pub mod asset;
 pub mod error;
 pub mod helper;
 pub mod querier;
+pub mod registry;
 pub mod types;
 
 #[cfg(test)]
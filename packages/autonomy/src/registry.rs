@@ -0,0 +1,159 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::Asset;
+use crate::error::CommonError;
+use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, StdResult};
+
+/// Registry queries served to the targets of the requests
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistryQueryMsg {
+    /// Get the request being executed
+    CurrentExecution {},
+}
+
+/// Request the registry is executing
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct CurrentExecution {
+    /// Id of the request
+    pub id: u64,
+    /// Owner of the request
+    pub user: String,
+    /// Target contract called by the request
+    pub target: String,
+    /// Assets forwarded to the target
+    pub input_assets: Vec<Asset>,
+}
+
+/// Response for query the current execution, `execution` is `None` when idle
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct CurrentExecutionResponse {
+    pub execution: Option<CurrentExecution>,
+}
+
+/// Returns the request the registry is executing, if any.
+/// ## Params
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **registry** is an object of type [`Addr`] which is the registry contract address.
+pub fn query_current_execution<C: CustomQuery>(
+    querier: &QuerierWrapper<C>,
+    registry: &Addr,
+) -> StdResult<Option<CurrentExecution>> {
+    let res: CurrentExecutionResponse =
+        querier.query_wasm_smart(registry, &RegistryQueryMsg::CurrentExecution {})?;
+    Ok(res.execution)
+}
+
+/// Asserts that the contract at `target` is called by the `registry` executing a request of `user`.
+/// Returns the [`CurrentExecution`] if successful, otherwise returns [`CommonError::Unauthorized`].
+/// ## Params
+/// * **querier** is an object of type [`QuerierWrapper`].
+///
+/// * **registry** is an object of type [`Addr`] which is the registry contract address.
+///
+/// * **sender** is an object of type [`Addr`] which is the caller of the target.
+///
+/// * **target** is an object of type [`Addr`] which is the contract being called.
+///
+/// * **user** is an object of type [`Addr`] which is the expected owner of the request.
+pub fn assert_registry_execution<C: CustomQuery>(
+    querier: &QuerierWrapper<C>,
+    registry: &Addr,
+    sender: &Addr,
+    target: &Addr,
+    user: &Addr,
+) -> Result<CurrentExecution, CommonError> {
+    if sender != registry {
+        return Err(CommonError::Unauthorized {});
+    }
+
+    match query_current_execution(querier, registry)? {
+        Some(execution) if execution.target == *target && execution.user == *user => Ok(execution),
+        _ => Err(CommonError::Unauthorized {}),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{from_binary, to_binary, ContractResult, SystemResult, WasmQuery};
+
+    fn mock_querier(execution: Option<CurrentExecution>) -> MockQuerier {
+        let mut querier = MockQuerier::new(&[]);
+        querier.update_wasm(move |request| match request {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "registry" => {
+                let RegistryQueryMsg::CurrentExecution {} = from_binary(msg).unwrap();
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&CurrentExecutionResponse {
+                        execution: execution.clone(),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => panic!("[mock]: unsupported wasm query {:?}", request),
+        });
+        querier
+    }
+
+    #[test]
+    fn test_assert_registry_execution() {
+        let execution = CurrentExecution {
+            id: 1,
+            user: "user".to_string(),
+            target: "wrapper".to_string(),
+            input_assets: vec![],
+        };
+        let querier = mock_querier(Some(execution.clone()));
+        let querier: QuerierWrapper = QuerierWrapper::new(&querier);
+        let registry = Addr::unchecked("registry");
+        let wrapper = Addr::unchecked("wrapper");
+        let user = Addr::unchecked("user");
+
+        assert_eq!(
+            assert_registry_execution(&querier, &registry, &registry, &wrapper, &user),
+            Ok(execution)
+        );
+
+        // Not called by the registry
+        assert_eq!(
+            assert_registry_execution(&querier, &registry, &user, &wrapper, &user),
+            Err(CommonError::Unauthorized {})
+        );
+
+        // Executing a request of someone else
+        assert_eq!(
+            assert_registry_execution(
+                &querier,
+                &registry,
+                &registry,
+                &wrapper,
+                &Addr::unchecked("other")
+            ),
+            Err(CommonError::Unauthorized {})
+        );
+
+        // Executing a request on another target
+        assert_eq!(
+            assert_registry_execution(
+                &querier,
+                &registry,
+                &registry,
+                &Addr::unchecked("other"),
+                &user
+            ),
+            Err(CommonError::Unauthorized {})
+        );
+
+        // Idle registry
+        let querier = mock_querier(None);
+        let querier: QuerierWrapper = QuerierWrapper::new(&querier);
+        assert_eq!(
+            assert_registry_execution(&querier, &registry, &registry, &wrapper, &user),
+            Err(CommonError::Unauthorized {})
+        );
+    }
+}
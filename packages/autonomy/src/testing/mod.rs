@@ -2,5 +2,4 @@ mod custom_mock_querier;
 mod cw20_querier;
 mod helpers;
 
-pub use custom_mock_querier::CustomMockQuerier;
 pub use helpers::mock_dependencies;